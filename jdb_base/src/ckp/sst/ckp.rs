@@ -24,20 +24,40 @@ pub enum Op {
     /// 移除 SST 列表
     rm: Vec<(Level, Vec<u64>)>,
   },
+  /// Flush MemTable to SST with the WAL it came from.
+  /// Appended as a new variant so manifests written before it still decode
+  /// 刷写 MemTable 到 SST，并记录其来源 WAL。
+  /// 作为新变体追加，使此前写入的清单仍可解码
+  Mem2SstWal {
+    /// New SST metadata
+    /// 新 SST 元数据
+    meta: Meta,
+    /// WAL of the flushed memtable, replay starts after it
+    /// 已刷盘内存表的 WAL，回放从它之后开始
+    wal_id: u64,
+  },
 }
 
 /// Interface for updating Levels state
 /// 更新 Levels 状态的接口
 pub trait Levels {
-  /// Update state with operation (apply only to memory)
-  /// 使用操作更新状态（仅应用到内存）
-  fn update(&mut self, op: Op);
+  /// Error type
+  /// 错误类型
+  type Error: Debug + Send;
+
+  /// Check operation can be applied, without changing state
+  /// 检查操作能否应用，不改变状态
+  fn verify(&self, op: &Op) -> Result<(), Self::Error>;
+
+  /// Update state with operation (apply only to memory), the op must pass `verify` first
+  /// 使用操作更新状态（仅应用到内存），操作须先通过 `verify`
+  fn update(&mut self, op: Op) -> Result<(), Self::Error>;
 }
 
 pub trait Ckp: Send + 'static {
   /// Error type
   /// 错误类型
-  type Error: Debug + Send;
+  type Error: Debug + Send + From<<Self::Levels as Levels>::Error>;
 
   /// Internal Levels state type
   /// 内部 Levels 状态类型
@@ -47,12 +67,15 @@ pub trait Ckp: Send + 'static {
   /// 获取内部 Levels 状态（可变）
   fn levels_mut(&mut self) -> &mut Self::Levels;
 
-  /// Apply operation atomically (write disk first, then update memory)
-  /// 原子应用操作（先写入磁盘，再更新内存）
+  /// Apply operation atomically (verify, write disk, then update memory)
+  /// 原子应用操作（校验，写入磁盘，再更新内存）
   fn apply(&mut self, op: Op) -> impl Future<Output = Result<(), Self::Error>> + Send {
     async move {
+      // Reject invalid op before it reaches disk
+      // 在写入磁盘前拒绝无效操作
+      self.levels_mut().verify(&op)?;
       self.write(bitcode::encode(&op)).await?;
-      self.levels_mut().update(op);
+      self.levels_mut().update(op)?;
       Ok(())
     }
   }
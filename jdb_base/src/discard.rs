@@ -14,6 +14,13 @@ pub trait Discard: 'static {
   /// 当 key-value 被丢弃时调用（老版本或最底层墓碑）
   fn discard(&mut self, key: &[u8], pos: &Pos);
 
+  /// Value file `wal_id` (`Pos.wal_id`) holds at least `size` bytes: the furthest value end
+  /// a flushed memtable saw in it, called before that flush's discards. Ignored by default
+  /// 值文件 `wal_id`（`Pos.wal_id`）至少有 `size` 字节：已刷盘内存表在其中见到的最远值末尾，
+  /// 在该次刷盘的丢弃项之前调用。默认忽略
+  #[inline]
+  fn wal_size(&mut self, _wal_id: u64, _size: u64) {}
+
   /// Flush discard buffer to storage
   /// 将丢弃缓冲区刷入存储
   fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>>;
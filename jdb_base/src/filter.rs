@@ -0,0 +1,187 @@
+//! Compaction filter: user hook to keep, drop or rewrite entries during merge
+//! 压缩过滤器：合并时保留、丢弃或改写条目的用户钩子
+
+use std::{
+  borrow::Borrow,
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use futures_core::Stream;
+
+use crate::{Discard, Pos};
+
+/// Filter decision for one entry
+/// 单个条目的过滤决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+  /// Keep entry as is
+  /// 原样保留
+  Keep,
+  /// Drop entry, reported to Discard.
+  /// It vanishes only at the bottom level with no pinned snapshot older than it,
+  /// otherwise older versions may remain (below, or kept for a snapshot by `Purge`)
+  /// and it becomes a tombstone to shadow them
+  /// 丢弃条目，上报给 Discard。
+  /// 仅在最底层且没有比它更老的固定快照时才直接消失，
+  /// 否则可能仍有更旧的版本（在下层，或被 `Purge` 为快照保留），此时变为墓碑以遮蔽它们
+  Drop,
+  /// Replace position, old one reported to Discard if moved
+  /// 替换位置，若位置变化则旧位置上报给 Discard
+  Rewrite(Pos),
+}
+
+/// Compaction filter trait (e.g. TTL expiry, purge deleted tenants)
+/// 压缩过滤器 trait（如 TTL 过期、清除已删除租户）
+pub trait Filter: 'static {
+  /// Decide entry fate, tombstones are never passed in:
+  /// dropping one could resurrect older versions, `Purge` removes them at the bottom instead
+  /// 决定条目去留，墓碑不会传入：
+  /// 丢弃墓碑可能使旧版本复活，改由 `Purge` 在最底层移除
+  fn filter(&mut self, key: &[u8], pos: &Pos) -> Decision;
+}
+
+/// Keep everything
+/// 全部保留
+impl Filter for () {
+  #[inline]
+  fn filter(&mut self, _: &[u8], _: &Pos) -> Decision {
+    Decision::Keep
+  }
+}
+
+impl<F: FnMut(&[u8], &Pos) -> Decision + 'static> Filter for F {
+  #[inline]
+  fn filter(&mut self, key: &[u8], pos: &Pos) -> Decision {
+    self(key, pos)
+  }
+}
+
+/// Apply filter to one entry, None if dropped.
+/// `bottom`: no older data below the output, see `Merge::bottom`.
+/// `snaps`: pinned snapshot versions, ascending, as given to `Purge`
+/// 对单个条目应用过滤器，丢弃时返回 None。
+/// `bottom`：输出之下没有更旧的数据，见 `Merge::bottom`。
+/// `snaps`：已固定的快照版本，升序，与传给 `Purge` 的相同
+#[inline]
+pub fn apply<F: Filter, D: Discard>(
+  filter: &mut F,
+  discard: &mut D,
+  key: &[u8],
+  pos: Pos,
+  bottom: bool,
+  snaps: &[u64],
+) -> Option<Pos> {
+  if pos.flag.is_tombstone() {
+    return Some(pos);
+  }
+  match filter.filter(key, &pos) {
+    Decision::Keep => Some(pos),
+    Decision::Drop => {
+      discard.discard(key, &pos);
+      // Older versions may live below, or follow in the output for a snapshot
+      // that cannot see this one, keep a tombstone over them
+      // 更旧的版本可能在下层，或为看不到本版本的快照保留在输出中，保留墓碑遮蔽它们
+      let covered = snaps.first().is_some_and(|&snap| snap < pos.ver);
+      (!bottom || covered).then(|| pos.to_tombstone())
+    }
+    Decision::Rewrite(new) => {
+      // Old bytes are garbage once the value lives elsewhere
+      // 值迁移到别处后，旧字节成为垃圾
+      if (new.wal_id, new.offset_or_file_id) != (pos.wal_id, pos.offset_or_file_id) {
+        discard.discard(key, &pos);
+      }
+      Some(new)
+    }
+  }
+}
+
+/// Merge iterator/stream adaptor applying a Filter
+/// 应用 Filter 的合并迭代器/流适配器
+pub struct Filtered<'a, I, F, D> {
+  pub inner: I,
+  pub filter: &'a mut F,
+  pub discard: &'a mut D,
+  /// Output is the bottom, dropped entries may vanish instead of turning into tombstones
+  /// 输出为最底层，被丢弃的条目可直接消失而非变为墓碑
+  pub bottom: bool,
+  /// Pinned snapshot versions, ascending, see `apply`
+  /// 已固定的快照版本，升序，见 `apply`
+  pub snaps: &'a [u64],
+}
+
+impl<'a, I, F, D> Filtered<'a, I, F, D> {
+  #[inline]
+  pub fn new(
+    inner: I,
+    filter: &'a mut F,
+    discard: &'a mut D,
+    bottom: bool,
+    snaps: &'a [u64],
+  ) -> Self {
+    Self {
+      inner,
+      filter,
+      discard,
+      bottom,
+      snaps,
+    }
+  }
+}
+
+impl<K, I, F, D> Iterator for Filtered<'_, I, F, D>
+where
+  K: Borrow<[u8]>,
+  I: Iterator<Item = (K, Pos)>,
+  F: Filter,
+  D: Discard,
+{
+  type Item = (K, Pos);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    for (key, pos) in self.inner.by_ref() {
+      if let Some(pos) = apply(
+        self.filter,
+        self.discard,
+        key.borrow(),
+        pos,
+        self.bottom,
+        self.snaps,
+      ) {
+        return Some((key, pos));
+      }
+    }
+    None
+  }
+}
+
+impl<K, I, F, D> Stream for Filtered<'_, I, F, D>
+where
+  K: Borrow<[u8]>,
+  I: Stream<Item = (K, Pos)> + Unpin,
+  F: Filter,
+  D: Discard,
+{
+  type Item = (K, Pos);
+
+  fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+    let this = self.get_mut();
+    loop {
+      match Pin::new(&mut this.inner).poll_next(cx) {
+        Poll::Ready(Some((key, pos))) => {
+          if let Some(pos) = apply(
+            this.filter,
+            this.discard,
+            key.borrow(),
+            pos,
+            this.bottom,
+            this.snaps,
+          ) {
+            return Poll::Ready(Some((key, pos)));
+          }
+        }
+        other => return other,
+      }
+    }
+  }
+}
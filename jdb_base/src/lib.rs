@@ -6,10 +6,17 @@ pub use mem::Mem;
 mod pos;
 pub use pos::Pos;
 mod discard;
+pub mod filter;
 pub mod order;
+pub mod purge;
 pub mod query;
+pub mod snapshot;
 pub mod sst;
+pub mod stall;
 pub use discard::Discard;
+pub use filter::{Decision, Filter};
+pub use purge::Purge;
+pub use snapshot::{Snapshot, Snapshots};
 
 /// Type alias for existence check result
 /// 存在性检查结果的类型别名
@@ -1,4 +1,4 @@
-use core::ops::RangeBounds;
+use core::ops::{Range, RangeBounds};
 use std::borrow::Borrow;
 
 use crate::pos::Pos;
@@ -20,10 +20,30 @@ pub trait Mem {
 
   fn put(
     &mut self,
-    key: impl Into<Box<[u8]>>,
+    key: impl Borrow<[u8]>,
     pos: Pos,
   ) -> impl std::future::Future<Output = Result<(), Self::Error>>;
 
+  /// Delete key, pos locates the delete record and is stored as a tombstone
+  /// 删除键，pos 指向删除记录并以墓碑形式存储
+  fn delete(
+    &mut self,
+    key: impl Borrow<[u8]>,
+    pos: Pos,
+  ) -> impl std::future::Future<Output = Result<(), Self::Error>> {
+    self.put(key, pos.to_tombstone())
+  }
+
+  /// Delete keys in [start, end) written before pos.ver
+  /// 删除 [start, end) 中早于 pos.ver 写入的键
+  fn delete_range<K: Borrow<[u8]>>(
+    &mut self,
+    range: Range<K>,
+    pos: Pos,
+  ) -> impl std::future::Future<Output = Result<(), Self::Error>>;
+
+  /// Get position by key, deleted keys give a tombstone
+  /// 按键获取位置，已删除的键返回墓碑
   fn get(&self, key: impl Borrow<[u8]>) -> Option<Pos>;
 
   fn sync(&mut self) -> impl std::future::Future<Output = Result<(), Self::Error>>;
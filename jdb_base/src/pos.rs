@@ -15,4 +15,38 @@ pub struct Pos {
 
 impl Pos {
   pub const SIZE: usize = std::mem::size_of::<Self>();
+
+  #[inline]
+  pub const fn new(ver: u64, flag: Flag, wal_id: u64, offset_or_file_id: u64, len: u32) -> Self {
+    Self {
+      ver,
+      wal_id,
+      offset_or_file_id,
+      len,
+      flag,
+    }
+  }
+
+  /// Same position marked as deleted
+  /// 标记为已删除的相同位置
+  #[inline]
+  pub const fn to_tombstone(self) -> Self {
+    Self {
+      flag: self.flag.tombstone(),
+      ..self
+    }
+  }
+
+  #[inline]
+  pub const fn is_tombstone(self) -> bool {
+    self.flag.is_tombstone()
+  }
+
+  /// Value length
+  /// 值长度
+  #[inline]
+  #[allow(clippy::len_without_is_empty)]
+  pub const fn len(self) -> u32 {
+    self.len
+  }
 }
@@ -0,0 +1,131 @@
+//! Drop shadowed versions and bottommost tombstones during merge
+//! 合并时丢弃被遮蔽的版本和最底层墓碑
+//!
+//! Input is sorted by key, versions of one key newest first.
+//! A version is kept only if it is the newest one visible to the latest
+//! view or to some pinned snapshot. When merging into the bottom (nothing
+//! older below), a tombstone with no kept version beneath it shadows
+//! nothing and is dropped too.
+//! 输入按键排序，同一键的版本从新到旧。
+//! 仅当某版本是最新视图或某个固定快照可见的最新版本时才保留。
+//! 合并到最底层（下方没有更老数据）时，其下方没有保留版本的墓碑不再遮蔽任何数据，也会被丢弃。
+
+use std::{borrow::Borrow, collections::VecDeque, iter::Peekable};
+
+use crate::{Discard, Pos};
+
+/// Bytes one entry takes in an SST (key + Pos)
+/// 单个条目在 SST 中占用的字节数（键 + Pos）
+#[inline]
+pub fn entry_size(key: &[u8]) -> u64 {
+  (key.len() + Pos::SIZE) as u64
+}
+
+/// Merge iterator adaptor removing garbage versions
+/// 移除垃圾版本的合并迭代器适配器
+pub struct Purge<'a, K, I: Iterator<Item = (K, Pos)>, D> {
+  inner: Peekable<I>,
+  /// Pinned snapshot versions, ascending
+  /// 已固定的快照版本，升序
+  snaps: &'a [u64],
+  bottom: bool,
+  discard: &'a mut D,
+  out: VecDeque<(K, Pos)>,
+  /// Bytes of garbage still kept (for output `Sst::rmed`)
+  /// 仍保留的垃圾字节数（用于输出的 `Sst::rmed`）
+  pub rmed: u64,
+}
+
+impl<'a, K, I, D> Purge<'a, K, I, D>
+where
+  K: Borrow<[u8]>,
+  I: Iterator<Item = (K, Pos)>,
+  D: Discard,
+{
+  /// `snaps` must be ascending (see `Snapshots::vers`),
+  /// `bottom` is true when no older data lies below the output.
+  /// A `Filtered` on the output must get the same `snaps` and `bottom`
+  /// `snaps` 须为升序（见 `Snapshots::vers`），输出下方无更老数据时 `bottom` 为 true。
+  /// 输出上的 `Filtered` 须使用相同的 `snaps` 和 `bottom`
+  #[inline]
+  pub fn new(inner: I, snaps: &'a [u64], bottom: bool, discard: &'a mut D) -> Self {
+    Self {
+      inner: inner.peekable(),
+      snaps,
+      bottom,
+      discard,
+      out: VecDeque::new(),
+      rmed: 0,
+    }
+  }
+
+  /// Snapshots that can see ver share a stripe, only the newest version
+  /// of each stripe is needed
+  /// 能看到 ver 的快照属于同一条带，每个条带只需要最新版本
+  #[inline]
+  fn stripe(&self, ver: u64) -> usize {
+    self.snaps.partition_point(|s| *s < ver)
+  }
+
+  /// Load and purge all versions of the next key
+  /// 加载并清理下一个键的所有版本
+  fn fill(&mut self) {
+    let Some((key, pos)) = self.inner.next() else {
+      return;
+    };
+    let mut last = self.stripe(pos.ver);
+    self.out.push_back((key, pos));
+    while let Some((next, _)) = self.inner.peek() {
+      if next.borrow() != self.out[0].0.borrow() {
+        break;
+      }
+      let Some((next, pos)) = self.inner.next() else {
+        break;
+      };
+      let stripe = self.stripe(pos.ver);
+      if stripe == last {
+        self.discard.discard(next.borrow(), &pos);
+      } else {
+        last = stripe;
+        self.out.push_back((next, pos));
+      }
+    }
+
+    if self.bottom {
+      while let Some((key, pos)) = self.out.back() {
+        if !pos.flag.is_tombstone() {
+          break;
+        }
+        self.discard.discard(key.borrow(), pos);
+        self.out.pop_back();
+      }
+    }
+
+    // Everything but the latest live version is garbage
+    // 除最新的有效版本外都是垃圾
+    for (i, (key, pos)) in self.out.iter().enumerate() {
+      if i > 0 || pos.flag.is_tombstone() {
+        self.rmed += entry_size(key.borrow());
+      }
+    }
+  }
+}
+
+impl<K, I, D> Iterator for Purge<'_, K, I, D>
+where
+  K: Borrow<[u8]>,
+  I: Iterator<Item = (K, Pos)>,
+  D: Discard,
+{
+  type Item = (K, Pos);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(item) = self.out.pop_front() {
+        return Some(item);
+      }
+      self.inner.peek()?;
+      self.fill();
+    }
+  }
+}
@@ -0,0 +1,58 @@
+//! Pinned read snapshots, protecting versions from compaction
+//! 固定的读快照，防止版本被压缩清除
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+/// Registry of pinned snapshot versions (ver -> pin count)
+/// 已固定快照版本的登记表（ver -> 固定次数）
+#[derive(Debug, Clone, Default)]
+pub struct Snapshots(Rc<RefCell<BTreeMap<u64, usize>>>);
+
+impl Snapshots {
+  /// Pin a version, it stays readable until the guard drops
+  /// 固定一个版本，在守卫释放前保持可读
+  pub fn pin(&self, ver: u64) -> Snapshot {
+    *self.0.borrow_mut().entry(ver).or_default() += 1;
+    Snapshot {
+      ver,
+      snaps: self.clone(),
+    }
+  }
+
+  /// Pinned versions, ascending
+  /// 已固定的版本，升序
+  pub fn vers(&self) -> Vec<u64> {
+    self.0.borrow().keys().copied().collect()
+  }
+
+  /// Oldest pinned version
+  /// 最老的固定版本
+  #[inline]
+  pub fn oldest(&self) -> Option<u64> {
+    self.0.borrow().keys().next().copied()
+  }
+
+  fn unpin(&self, ver: u64) {
+    let mut map = self.0.borrow_mut();
+    if let Some(n) = map.get_mut(&ver) {
+      *n -= 1;
+      if *n == 0 {
+        map.remove(&ver);
+      }
+    }
+  }
+}
+
+/// Pinned snapshot guard, unpins on drop
+/// 已固定快照的守卫，释放时解除固定
+#[derive(Debug)]
+pub struct Snapshot {
+  pub ver: u64,
+  snaps: Snapshots,
+}
+
+impl Drop for Snapshot {
+  fn drop(&mut self) {
+    self.snaps.unpin(self.ver);
+  }
+}
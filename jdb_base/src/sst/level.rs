@@ -21,6 +21,33 @@ impl Level {
       None
     }
   }
+
+  /// Level above, None for L0
+  /// 上一层，L0 时为 None
+  #[inline]
+  pub fn prev(&self) -> Option<Self> {
+    Self::try_from((*self as u8).checked_sub(1)?).ok()
+  }
+}
+
+impl TryFrom<u8> for Level {
+  type Error = u8;
+
+  /// Checked conversion, returns the value back if it is not a level
+  /// 带检查的转换，不是层级时原样返回该值
+  #[inline]
+  fn try_from(v: u8) -> Result<Self, u8> {
+    Ok(match v {
+      0 => Self::L0,
+      1 => Self::L1,
+      2 => Self::L2,
+      3 => Self::L3,
+      4 => Self::L4,
+      5 => Self::L5,
+      6 => Self::L6,
+      _ => return Err(v),
+    })
+  }
 }
 
 impl From<Level> for usize {
@@ -4,19 +4,28 @@ use crate::{Pos, ckp::sst::Meta};
 
 pub type Kv<'a> = (&'a [u8], Pos);
 
+/// Range tombstone (start, end, pos): keys in [start, end) older than pos.ver are deleted
+/// 范围墓碑 (start, end, pos)：[start, end) 中早于 pos.ver 的键被删除
+pub type RangeDel<'a> = (&'a [u8], &'a [u8], Pos);
+
 /// Flush memtable to SST
 /// 将内存表刷到 SST
 pub trait MemToSst: Send + 'static {
   type Error: Send + Debug;
 
-  /// Flush memtable to disk
-  /// 将内存表刷到磁盘
+  /// Flush memtable entries and its range tombstones to disk
+  /// 将内存表条目及其范围墓碑刷到磁盘
   fn write<'a>(
     &self,
     iter: impl Iterator<Item = Kv<'a>>,
+    range_del: impl Iterator<Item = RangeDel<'a>>,
   ) -> impl Future<Output = Result<Meta, Self::Error>>;
 
-  /// Ensure remove mem and add sst are seamless (no await in between)
-  /// 确保移除mem和添加sst无缝操作（之间没有await）
-  fn push(&mut self, meta: Meta);
+  /// Ensure remove mem and add sst are seamless (no await in between),
+  /// `wal_id` is the log of the flushed memtable, record it as `Op::Mem2SstWal::wal_id`
+  /// and pass `Levels::wal_id` to `Mem::gc_wal` once that write is durable
+  /// 确保移除mem和添加sst无缝操作（之间没有await），
+  /// `wal_id` 是已刷盘内存表的日志，记录为 `Op::Mem2SstWal::wal_id`，
+  /// 该写入持久化后将 `Levels::wal_id` 传给 `Mem::gc_wal`
+  fn push(&mut self, meta: Meta, wal_id: u64);
 }
@@ -3,7 +3,7 @@ pub mod mem_to_sst;
 pub mod meta;
 pub mod query;
 pub use level::Level;
-pub use mem_to_sst::{Kv, MemToSst};
+pub use mem_to_sst::{Kv, MemToSst, RangeDel};
 pub use meta::Meta;
 pub use query::Query;
 
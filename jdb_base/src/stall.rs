@@ -0,0 +1,101 @@
+//! Write stall signal from compaction state to writers
+//! 从压缩状态传给写入方的写入停顿信号
+
+use std::{
+  cell::{Cell, RefCell},
+  future::poll_fn,
+  rc::Rc,
+  task::{Poll, Waker},
+};
+
+/// Why writes are slowed down
+/// 写入被减速的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+  /// Too many L0 sublevels
+  /// L0 子层过多
+  L0Files,
+  /// Too many bytes waiting for compaction
+  /// 待压缩字节数过多
+  PendingBytes,
+}
+
+/// Write stall state
+/// 写入停顿状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Stall {
+  #[default]
+  None,
+  /// Delay each write a little
+  /// 每次写入稍作延迟
+  Delay(Reason),
+  /// Block writes until compaction catches up
+  /// 阻塞写入直到压缩追上
+  Stop(Reason),
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+  stall: Cell<Stall>,
+  /// Bumped whenever wakers are drained, so waiters know their slot is gone
+  /// 每次清空唤醒器时递增，让等待者知道其槽位已失效
+  round: Cell<u64>,
+  /// One waker per waiting future
+  /// 每个等待中的 future 一个唤醒器
+  wakers: RefCell<Vec<Waker>>,
+}
+
+/// Shared stall signal, set by levels, read by memtable
+/// 共享的停顿信号，由层级设置，由内存表读取
+#[derive(Debug, Clone, Default)]
+pub struct Signal(Rc<Inner>);
+
+impl Signal {
+  /// Current stall state
+  /// 当前停顿状态
+  #[inline]
+  pub fn get(&self) -> Stall {
+    self.0.stall.get()
+  }
+
+  /// Update stall state, wake blocked writers once no longer stopped
+  /// 更新停顿状态，解除停止后唤醒被阻塞的写入方
+  pub fn set(&self, stall: Stall) {
+    self.0.stall.set(stall);
+    if !matches!(stall, Stall::Stop(_)) {
+      self.0.round.set(self.0.round.get() + 1);
+      for waker in self.0.wakers.take() {
+        waker.wake();
+      }
+    }
+  }
+
+  /// Wait until writes are no longer stopped
+  /// 等待直到写入不再被停止
+  pub async fn wait(&self) {
+    // (round, index) of this waiter's waker
+    // 该等待者唤醒器的（轮次，索引）
+    let mut slot = None;
+    poll_fn(|cx| {
+      if !matches!(self.get(), Stall::Stop(_)) {
+        return Poll::Ready(());
+      }
+      let round = self.0.round.get();
+      let mut wakers = self.0.wakers.borrow_mut();
+      match slot {
+        Some((r, i)) if r == round => {
+          let waker: &mut Waker = &mut wakers[i];
+          if !waker.will_wake(cx.waker()) {
+            waker.clone_from(cx.waker());
+          }
+        }
+        _ => {
+          slot = Some((round, wakers.len()));
+          wakers.push(cx.waker().clone());
+        }
+      }
+      Poll::Pending
+    })
+    .await
+  }
+}
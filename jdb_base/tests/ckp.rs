@@ -0,0 +1,62 @@
+use bitcode::{Decode, Encode};
+use jdb_base::{
+  ckp::sst::{Meta, Sst, ckp::Op},
+  sst::{self, Level},
+};
+
+/// `Op` as written by manifests before WAL ids
+/// WAL id 之前的清单所写入的 `Op`
+#[derive(Encode, Decode)]
+enum OldOp {
+  Mem2Sst {
+    meta: Meta,
+  },
+  Compact {
+    add: Vec<Meta>,
+    rm: Vec<(Level, Vec<u64>)>,
+  },
+}
+
+fn new_meta(id: u64) -> Meta {
+  Meta {
+    sst: Sst {
+      level: Level::L0,
+      rmed: 1,
+      size: 10,
+    },
+    meta: sst::Meta {
+      id,
+      min: b"a".as_slice().into(),
+      max: b"z".as_slice().into(),
+    },
+  }
+}
+
+#[test]
+fn test_decode_old_op() {
+  let old = bitcode::encode(&OldOp::Mem2Sst { meta: new_meta(7) });
+  let Op::Mem2Sst { meta } = bitcode::decode::<Op>(&old).unwrap() else {
+    panic!("expect Op::Mem2Sst");
+  };
+  assert_eq!(meta.meta.id, 7);
+  assert_eq!(meta.sst.size, 10);
+
+  let old = bitcode::encode(&OldOp::Compact {
+    add: vec![new_meta(8)],
+    rm: vec![(Level::L0, vec![7])],
+  });
+  let Op::Compact { add, rm } = bitcode::decode::<Op>(&old).unwrap() else {
+    panic!("expect Op::Compact");
+  };
+  assert_eq!(add[0].meta.id, 8);
+  assert_eq!(rm, vec![(Level::L0, vec![7])]);
+
+  let new = bitcode::encode(&Op::Mem2SstWal {
+    meta: new_meta(9),
+    wal_id: 3,
+  });
+  let Op::Mem2SstWal { meta, wal_id } = bitcode::decode::<Op>(&new).unwrap() else {
+    panic!("expect Op::Mem2SstWal");
+  };
+  assert_eq!((meta.meta.id, wal_id), (9, 3));
+}
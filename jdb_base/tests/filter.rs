@@ -0,0 +1,114 @@
+use jdb_base::{Decision, Discard, Flag, Pos, filter::Filtered};
+
+#[derive(Default)]
+struct Record(Vec<(Vec<u8>, u64)>);
+
+impl Discard for Record {
+  type Error = ();
+
+  fn discard(&mut self, key: &[u8], pos: &Pos) {
+    self.0.push((key.to_vec(), pos.offset_or_file_id));
+  }
+
+  async fn flush(&mut self) -> Result<(), ()> {
+    Ok(())
+  }
+}
+
+fn pos(offset: u64, flag: Flag) -> Pos {
+  Pos {
+    ver: 1,
+    wal_id: 1,
+    offset_or_file_id: offset,
+    len: 8,
+    flag,
+  }
+}
+
+#[test]
+fn test_filter() {
+  let li = vec![
+    (b"keep".to_vec(), pos(1, Flag::INFILE)),
+    (b"drop".to_vec(), pos(2, Flag::INFILE)),
+    (b"drop".to_vec(), pos(3, Flag::INFILE.tombstone())),
+    (b"move".to_vec(), pos(4, Flag::INFILE)),
+  ];
+
+  let mut filter = |key: &[u8], pos: &Pos| match key {
+    b"drop" => Decision::Drop,
+    b"move" => Decision::Rewrite(Pos {
+      offset_or_file_id: 40,
+      ..*pos
+    }),
+    _ => Decision::Keep,
+  };
+  let mut discard = Record::default();
+
+  let out: Vec<_> = Filtered::new(li.clone().into_iter(), &mut filter, &mut discard, true, &[])
+    .map(|(k, p)| (k, p.offset_or_file_id))
+    .collect();
+
+  // Tombstone is kept even though filter would drop its key
+  // 即使过滤器会丢弃该键，墓碑仍被保留
+  assert_eq!(
+    out,
+    vec![
+      (b"keep".to_vec(), 1),
+      (b"drop".to_vec(), 3),
+      (b"move".to_vec(), 40)
+    ]
+  );
+  assert_eq!(
+    discard.0,
+    vec![(b"drop".to_vec(), 2), (b"move".to_vec(), 4)]
+  );
+
+  // Above the bottom a dropped entry turns into a tombstone
+  // 在最底层之上，被丢弃的条目变为墓碑
+  let mut discard = Record::default();
+  let out: Vec<_> = Filtered::new(
+    li.clone().into_iter(),
+    &mut filter,
+    &mut discard,
+    false,
+    &[],
+  )
+  .map(|(k, p)| (k, p.offset_or_file_id, p.flag.is_tombstone()))
+  .collect();
+  assert_eq!(
+    out,
+    vec![
+      (b"keep".to_vec(), 1, false),
+      (b"drop".to_vec(), 2, true),
+      (b"drop".to_vec(), 3, true),
+      (b"move".to_vec(), 40, false)
+    ]
+  );
+  assert_eq!(discard.0[0], (b"drop".to_vec(), 2));
+
+  // At the bottom, a snapshot older than the entry may still need an older version
+  // kept beneath it, so the drop leaves a tombstone to hide that version from newer reads
+  // 在最底层，比该条目更老的快照可能仍需要其下保留的旧版本，
+  // 因此丢弃会留下墓碑，对更新的读取隐藏该版本
+  let mut discard = Record::default();
+  let out: Vec<_> = Filtered::new(
+    li.clone().into_iter(),
+    &mut filter,
+    &mut discard,
+    true,
+    &[0],
+  )
+  .filter(|(k, _)| k == b"drop")
+  .map(|(_, p)| (p.offset_or_file_id, p.flag.is_tombstone()))
+  .collect();
+  assert_eq!(out, vec![(2, true), (3, true)]);
+
+  // Snapshots at or above the entry see it, no older version is kept for them
+  // 不低于该条目的快照能看到它，不会为它们保留旧版本
+  let mut discard = Record::default();
+  let out: Vec<_> = Filtered::new(li.into_iter(), &mut filter, &mut discard, true, &[1, 5])
+    .filter(|(k, _)| k == b"drop")
+    .map(|(_, p)| p.offset_or_file_id)
+    .collect();
+  assert_eq!(out, vec![3]);
+}
@@ -0,0 +1,11 @@
+use jdb_base::sst::Level;
+
+#[test]
+fn test_prev() {
+  assert_eq!(Level::L0.prev(), None);
+  assert_eq!(Level::L1.prev(), Some(Level::L0));
+  assert_eq!(Level::L6.prev(), Some(Level::L5));
+  assert_eq!(Level::L6.next(), None);
+  assert_eq!(Level::try_from(6), Ok(Level::L6));
+  assert_eq!(Level::try_from(7), Err(7));
+}
@@ -0,0 +1,105 @@
+use jdb_base::{Discard, Flag, Pos, Purge, Snapshots};
+
+#[derive(Default)]
+struct Record(Vec<(Vec<u8>, u64)>);
+
+impl Discard for Record {
+  type Error = ();
+
+  fn discard(&mut self, key: &[u8], pos: &Pos) {
+    self.0.push((key.to_vec(), pos.ver));
+  }
+
+  async fn flush(&mut self) -> Result<(), ()> {
+    Ok(())
+  }
+}
+
+fn pos(ver: u64, flag: Flag) -> Pos {
+  Pos {
+    ver,
+    wal_id: 1,
+    offset_or_file_id: ver,
+    len: 8,
+    flag,
+  }
+}
+
+fn li() -> Vec<(Vec<u8>, Pos)> {
+  let del = Flag::INFILE.tombstone();
+  vec![
+    (b"a".to_vec(), pos(9, Flag::INFILE)),
+    (b"a".to_vec(), pos(5, Flag::INFILE)),
+    (b"a".to_vec(), pos(2, Flag::INFILE)),
+    (b"b".to_vec(), pos(8, del)),
+    (b"b".to_vec(), pos(3, Flag::INFILE)),
+    (b"c".to_vec(), pos(7, del)),
+  ]
+}
+
+type Li = Vec<(Vec<u8>, u64)>;
+
+fn run(snaps: &[u64], bottom: bool) -> (Li, Li, u64) {
+  let mut discard = Record::default();
+  let mut purge = Purge::new(li().into_iter(), snaps, bottom, &mut discard);
+  let out: Vec<_> = purge.by_ref().map(|(k, p)| (k, p.ver)).collect();
+  let rmed = purge.rmed;
+  (out, discard.0, rmed)
+}
+
+#[test]
+fn test_purge_bottom() {
+  let (out, dropped, rmed) = run(&[], true);
+  assert_eq!(out, vec![(b"a".to_vec(), 9)]);
+  assert_eq!(
+    dropped,
+    vec![
+      (b"a".to_vec(), 5),
+      (b"a".to_vec(), 2),
+      (b"b".to_vec(), 3),
+      (b"b".to_vec(), 8),
+      (b"c".to_vec(), 7),
+    ]
+  );
+  assert_eq!(rmed, 0);
+}
+
+#[test]
+fn test_purge_not_bottom() {
+  // Tombstones must stay, they may shadow older data below
+  // 墓碑必须保留，它们可能遮蔽下方的更老数据
+  let (out, _, rmed) = run(&[], false);
+  assert_eq!(
+    out,
+    vec![(b"a".to_vec(), 9), (b"b".to_vec(), 8), (b"c".to_vec(), 7),]
+  );
+  assert_eq!(rmed, 2 * (1 + Pos::SIZE as u64));
+}
+
+#[test]
+fn test_purge_snapshot() {
+  let snaps = Snapshots::default();
+  let s4 = snaps.pin(4);
+  let _s6 = snaps.pin(6);
+  let _s6b = snaps.pin(6);
+  assert_eq!(snaps.vers(), vec![4, 6]);
+
+  let (out, dropped, rmed) = run(&snaps.vers(), true);
+  // a@5 is seen by snapshot 6, a@2 and b@3 by snapshot 4, so b@8 must shadow b@3
+  // a@5 被快照 6 看到，a@2 和 b@3 被快照 4 看到，因此 b@8 必须遮蔽 b@3
+  assert_eq!(
+    out,
+    vec![
+      (b"a".to_vec(), 9),
+      (b"a".to_vec(), 5),
+      (b"a".to_vec(), 2),
+      (b"b".to_vec(), 8),
+      (b"b".to_vec(), 3),
+    ]
+  );
+  assert_eq!(dropped, vec![(b"c".to_vec(), 7)]);
+  assert_eq!(rmed, 4 * (1 + Pos::SIZE as u64));
+
+  drop(s4);
+  assert_eq!(snaps.oldest(), Some(6));
+}
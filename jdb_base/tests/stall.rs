@@ -0,0 +1,72 @@
+use std::{
+  future::Future,
+  pin::pin,
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+  task::{Context, Poll, Wake, Waker},
+};
+
+use jdb_base::stall::{Reason, Signal, Stall};
+
+/// Counts wake calls
+/// 统计唤醒次数
+#[derive(Default)]
+struct Count(AtomicUsize);
+
+impl Wake for Count {
+  fn wake(self: Arc<Self>) {
+    self.0.fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+#[test]
+fn test_wait_one_waker() {
+  let signal = Signal::default();
+  signal.set(Stall::Stop(Reason::L0Files));
+
+  let first = Arc::new(Count::default());
+  let second = Arc::new(Count::default());
+  let mut wait = pin!(signal.wait());
+
+  // Repolls replace the waiter's waker instead of piling up
+  // 重复轮询替换该等待者的唤醒器而不是堆积
+  for _ in 0..3 {
+    let waker = Waker::from(first.clone());
+    assert!(
+      wait
+        .as_mut()
+        .poll(&mut Context::from_waker(&waker))
+        .is_pending()
+    );
+  }
+  let waker = Waker::from(second.clone());
+  assert!(
+    wait
+      .as_mut()
+      .poll(&mut Context::from_waker(&waker))
+      .is_pending()
+  );
+
+  signal.set(Stall::None);
+  assert_eq!(first.0.load(Ordering::Relaxed), 0);
+  assert_eq!(second.0.load(Ordering::Relaxed), 1);
+  assert_eq!(
+    wait.as_mut().poll(&mut Context::from_waker(&waker)),
+    Poll::Ready(())
+  );
+
+  // Stopped again: the old slot is gone, a new one is taken
+  // 再次停止：旧槽位已失效，占用新槽位
+  signal.set(Stall::Stop(Reason::PendingBytes));
+  let mut wait = pin!(signal.wait());
+  assert!(
+    wait
+      .as_mut()
+      .poll(&mut Context::from_waker(&waker))
+      .is_pending()
+  );
+  signal.set(Stall::Delay(Reason::PendingBytes));
+  assert_eq!(second.0.load(Ordering::Relaxed), 2);
+}
@@ -25,10 +25,14 @@ pub(super) async fn flush_task(inner: Rc<UnsafeCell<Inner>>) {
     };
 
     let f_ptr: *mut FsFile = f;
+    let rate_limit = i.rate_limit.clone();
 
     let mut written = 0;
     while written < total_len {
       let chunk = (total_len - written).min(MAX_WRITE_SIZE);
+      if let Some((limit, io)) = &rate_limit {
+        limit.acquire(*io, chunk).await;
+      }
       // Safe: ptr valid during flush, chunk within bounds
       // 安全：刷盘期间 ptr 有效，chunk 在范围内
       let slice = unsafe { std::slice::from_raw_parts(ptr.add(written), chunk) };
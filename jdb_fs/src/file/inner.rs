@@ -6,7 +6,10 @@ use std::task::Waker;
 use compio_fs::File as FsFile;
 
 use super::buf::Buf;
-use crate::Pos;
+use crate::{
+  Pos,
+  rate_limit::{Io, RateLimit},
+};
 
 pub(super) struct Inner {
   pub(super) buf0: Buf,
@@ -17,6 +20,9 @@ pub(super) struct Inner {
   pub(super) file: Option<FsFile>,
   pub(super) waker: Option<Waker>,
   pub(super) ing: bool,
+  /// Optional write throttle and its budget
+  /// 可选的写入限速器及其预算
+  pub(super) rate_limit: Option<(RateLimit, Io)>,
 }
 
 impl Inner {
@@ -30,6 +36,7 @@ impl Inner {
       file: None,
       waker: None,
       ing: false,
+      rate_limit: None,
     }
   }
 
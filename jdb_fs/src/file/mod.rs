@@ -23,7 +23,10 @@ use consts::MAX_BUF_SIZE;
 use flush::flush_task;
 use inner::Inner;
 
-use crate::Pos;
+use crate::{
+  Pos,
+  rate_limit::{Io, RateLimit},
+};
 
 struct WaitFor<F: Fn(&Inner) -> bool> {
   inner: Rc<UnsafeCell<Inner>>,
@@ -71,6 +74,13 @@ impl BufFile {
     unsafe { &mut *self.inner.get() }
   }
 
+  /// Throttle background flush with a shared rate limiter
+  /// 使用共享限速器限制后台刷盘
+  #[inline]
+  pub fn set_rate_limit(&mut self, limit: RateLimit, io: Io) {
+    self.i().rate_limit = Some((limit, io));
+  }
+
   #[inline(always)]
   pub fn pos(&self) -> Pos {
     self.pos
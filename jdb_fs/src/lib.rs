@@ -10,12 +10,13 @@ mod fs;
 pub mod item;
 pub mod load;
 mod push;
+pub mod rate_limit;
 mod try_rm;
 
 pub type Len = usize;
 pub type Size = u64;
 pub type Pos = u64;
-pub use atom_write::AtomWrite;
+pub use atom_write::{AtomWrite, TMP};
 pub use auto_compact::AutoCompact;
 pub use buf::{buf_writer, buf_writer_with_pos};
 pub use compact::{Compact, IncrCount};
@@ -23,4 +24,5 @@ pub use file::BufFile;
 pub use fs::read_write;
 pub use item::{DataLen, Error, Item, Offset, Result, Row};
 pub use push::push_iter;
+pub use rate_limit::RateLimit;
 pub use try_rm::try_rm;
@@ -0,0 +1,190 @@
+//! Token bucket rate limiter for background I/O
+//! 后台 I/O 的令牌桶限速器
+//!
+//! Flush and compaction have separate budgets, shared by every writer
+//! on the same thread (single-threaded runtime, `Rc` is enough).
+//! 刷盘和压缩各有独立预算，由同一线程上的所有写入器共享
+//! （单线程运行时，`Rc` 即可）。
+
+use std::{
+  cell::RefCell,
+  fmt::Debug,
+  future::Future,
+  pin::Pin,
+  rc::Rc,
+  time::{Duration, Instant},
+};
+
+/// Time source of the limiter, replaced by a fake one in tests
+/// 限速器的时间源，测试中可替换为假时钟
+pub trait Clock: Debug {
+  fn now(&self) -> Instant;
+
+  fn sleep(&self, dur: Duration) -> Pin<Box<dyn Future<Output = ()>>>;
+}
+
+/// Runtime clock
+/// 运行时时钟
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sys;
+
+impl Clock for Sys {
+  #[inline]
+  fn now(&self) -> Instant {
+    Instant::now()
+  }
+
+  #[inline]
+  fn sleep(&self, dur: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+    Box::pin(compio_runtime::time::sleep(dur))
+  }
+}
+
+/// I/O kind, each has its own budget
+/// I/O 类型，各自拥有独立预算
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Io {
+  /// Memtable flush
+  /// 内存表刷盘
+  Flush = 0,
+  /// Background compaction
+  /// 后台压缩
+  Compact = 1,
+}
+
+/// Auto-tune compaction rate from pending compaction bytes
+/// 根据待压缩字节数自动调整压缩速率
+#[derive(Debug, Clone, Copy)]
+pub struct Tune {
+  /// Rate (bytes/s) when pending <= soft
+  /// 待压缩量 <= soft 时的速率（字节/秒）
+  pub min: u64,
+  /// Rate (bytes/s) when pending >= hard
+  /// 待压缩量 >= hard 时的速率（字节/秒）
+  pub max: u64,
+  /// Pending bytes to start speeding up
+  /// 开始加速的待压缩字节数
+  pub soft: u64,
+  /// Pending bytes to reach max rate
+  /// 达到最大速率的待压缩字节数
+  pub hard: u64,
+}
+
+impl Tune {
+  /// Linear rate between min and max by pending bytes
+  /// 按待压缩字节数在 min 和 max 之间线性取值
+  #[inline]
+  pub fn rate(&self, pending: u64) -> u64 {
+    if pending >= self.hard {
+      return self.max;
+    }
+    if pending <= self.soft {
+      return self.min;
+    }
+    let span = (self.max.saturating_sub(self.min)) as u128;
+    let step = span * (pending - self.soft) as u128 / (self.hard - self.soft) as u128;
+    self.min.saturating_add(step as u64)
+  }
+}
+
+/// Single token bucket, tokens may go negative (debt)
+/// 单个令牌桶，令牌可为负（欠账）
+#[derive(Debug)]
+struct Bucket {
+  /// Bytes per second, 0 means unlimited
+  /// 每秒字节数，0 表示不限速
+  rate: u64,
+  tokens: i64,
+  last: Instant,
+}
+
+impl Bucket {
+  #[inline]
+  fn new(rate: u64, now: Instant) -> Self {
+    Self {
+      rate,
+      tokens: rate as i64,
+      last: now,
+    }
+  }
+
+  /// Take bytes, return time to wait for the debt
+  /// 取走字节，返回偿还欠账需等待的时间
+  fn take(&mut self, bytes: u64, now: Instant) -> Duration {
+    if self.rate == 0 {
+      return Duration::ZERO;
+    }
+    let refill = now.duration_since(self.last).as_nanos() * self.rate as u128 / 1_000_000_000;
+    self.last = now;
+    // Burst is capped to one second of budget
+    // 突发上限为一秒的预算
+    self.tokens = (self.tokens as i128 + refill as i128).min(self.rate as i128) as i64;
+    self.tokens = self.tokens.saturating_sub(bytes as i64);
+    if self.tokens >= 0 {
+      return Duration::ZERO;
+    }
+    Duration::from_nanos(((-self.tokens) as u128 * 1_000_000_000 / self.rate as u128) as u64)
+  }
+}
+
+#[derive(Debug)]
+struct Inner {
+  bucket: RefCell<[Bucket; 2]>,
+  clock: Box<dyn Clock>,
+}
+
+/// Shared rate limiter for flush and compaction writes
+/// 刷盘和压缩写入共享的限速器
+#[derive(Debug, Clone)]
+pub struct RateLimit(Rc<Inner>);
+
+impl RateLimit {
+  /// Create with flush and compaction rate (bytes/s, 0 means unlimited)
+  /// 使用刷盘和压缩速率创建（字节/秒，0 表示不限速）
+  #[inline]
+  pub fn new(flush: u64, compact: u64) -> Self {
+    Self::with_clock(Sys, flush, compact)
+  }
+
+  /// Create with a custom time source
+  /// 使用自定义时间源创建
+  pub fn with_clock(clock: impl Clock + 'static, flush: u64, compact: u64) -> Self {
+    let now = clock.now();
+    Self(Rc::new(Inner {
+      bucket: RefCell::new([Bucket::new(flush, now), Bucket::new(compact, now)]),
+      clock: Box::new(clock),
+    }))
+  }
+
+  /// Get rate of I/O kind
+  /// 获取 I/O 类型的速率
+  #[inline]
+  pub fn rate(&self, io: Io) -> u64 {
+    self.0.bucket.borrow()[io as usize].rate
+  }
+
+  /// Set rate of I/O kind
+  /// 设置 I/O 类型的速率
+  #[inline]
+  pub fn set(&self, io: Io, rate: u64) {
+    self.0.bucket.borrow_mut()[io as usize].rate = rate;
+  }
+
+  /// Re-tune compaction rate from pending compaction bytes
+  /// 根据待压缩字节数重新调整压缩速率
+  #[inline]
+  pub fn tune(&self, tune: &Tune, pending: u64) {
+    self.set(Io::Compact, tune.rate(pending));
+  }
+
+  /// Wait until bytes fit in the budget
+  /// 等待直到字节数符合预算
+  pub async fn acquire(&self, io: Io, bytes: usize) {
+    let now = self.0.clock.now();
+    let wait = self.0.bucket.borrow_mut()[io as usize].take(bytes as u64, now);
+    if !wait.is_zero() {
+      self.0.clock.sleep(wait).await;
+    }
+  }
+}
@@ -0,0 +1,112 @@
+//! Tests for RateLimit
+//! RateLimit 测试
+
+use std::{
+  cell::Cell,
+  future::{Future, ready},
+  pin::Pin,
+  rc::Rc,
+  time::{Duration, Instant},
+};
+
+use compio::io::AsyncWrite;
+use jdb_fs::{
+  BufFile, RateLimit,
+  rate_limit::{Clock, Io, Tune},
+};
+use tempfile::tempdir;
+
+#[static_init::constructor(0)]
+extern "C" fn _log_init() {
+  log_init::init();
+}
+
+/// Fake clock: sleeping just moves time forward
+/// 假时钟：睡眠只是让时间前进
+#[derive(Debug, Clone)]
+struct Fake {
+  start: Instant,
+  slept: Rc<Cell<Duration>>,
+}
+
+impl Fake {
+  fn new() -> Self {
+    Self {
+      start: Instant::now(),
+      slept: Rc::default(),
+    }
+  }
+
+  fn elapsed(&self) -> Duration {
+    self.slept.get()
+  }
+}
+
+impl Clock for Fake {
+  fn now(&self) -> Instant {
+    self.start + self.slept.get()
+  }
+
+  fn sleep(&self, dur: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+    self.slept.set(self.slept.get() + dur);
+    Box::pin(ready(()))
+  }
+}
+
+#[compio::test]
+async fn test_acquire_throttle() {
+  // 1000 bytes/s, first second is burst
+  // 每秒 1000 字节，首秒为突发额度
+  let clock = Fake::new();
+  let limit = RateLimit::with_clock(clock.clone(), 0, 1000);
+  limit.acquire(Io::Compact, 1000).await;
+  assert_eq!(clock.elapsed(), Duration::ZERO);
+  limit.acquire(Io::Compact, 100).await;
+  assert_eq!(clock.elapsed(), Duration::from_millis(100));
+
+  // Flush is unlimited and independent
+  // 刷盘不限速且相互独立
+  limit.acquire(Io::Flush, 1 << 30).await;
+  assert_eq!(clock.elapsed(), Duration::from_millis(100));
+}
+
+#[test]
+fn test_tune() {
+  let tune = Tune {
+    min: 100,
+    max: 300,
+    soft: 1000,
+    hard: 2000,
+  };
+  assert_eq!(tune.rate(0), 100);
+  assert_eq!(tune.rate(1500), 200);
+  assert_eq!(tune.rate(5000), 300);
+
+  let limit = RateLimit::new(0, 0);
+  limit.tune(&tune, 2000);
+  assert_eq!(limit.rate(Io::Compact), 300);
+  assert_eq!(limit.rate(Io::Flush), 0);
+}
+
+#[compio::test]
+async fn test_buf_file_limit() {
+  let dir = tempdir().unwrap();
+  let file = compio_fs::OpenOptions::new()
+    .read(true)
+    .write(true)
+    .create(true)
+    .open(dir.path().join("test.dat"))
+    .await
+    .unwrap();
+  let mut f = BufFile::new(file, 0);
+  let clock = Fake::new();
+  f.set_rate_limit(RateLimit::with_clock(clock.clone(), 4096, 0), Io::Flush);
+
+  // 2048 bytes over the burst at 4096 bytes/s
+  // 超出突发额度 2048 字节，速率为每秒 4096 字节
+  let data = vec![1u8; 6144];
+  let compio::BufResult(r, _) = f.write(data).await;
+  r.unwrap();
+  f.flush().await;
+  assert_eq!(clock.elapsed(), Duration::from_millis(500));
+}
@@ -0,0 +1,98 @@
+//! Level shape configuration
+//! 层级形态配置
+
+use jdb_base::sst::Level;
+
+/// Level shape options
+/// 层级形态选项
+#[derive(Debug, Clone, Copy)]
+pub enum Conf {
+  /// Deepest level in use (L1-L6)
+  /// 使用的最深层级（L1-L6）
+  MaxLevel(Level),
+
+  /// Target size of the base level in bytes
+  /// 基础层的目标大小（字节）
+  BaseSize(u64),
+
+  /// Size multiplier per level
+  /// 每层的放大倍数
+  Fanout(u64),
+
+  /// L0 sublevel count to trigger compaction
+  /// L0 触发压缩的子层数
+  L0MaxFile(u64),
+}
+
+/// Level shape configuration
+/// 层级形态配置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+  /// Deepest level in use
+  /// 使用的最深层级
+  pub max_level: Level,
+
+  /// Base level target size
+  /// 基础层目标大小
+  pub base_size: u64,
+
+  /// Size multiplier per level
+  /// 每层放大倍数
+  pub fanout: u64,
+
+  /// L0 sublevel count to trigger compaction
+  /// L0 触发压缩的子层数
+  pub l0_max_file: u64,
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Self {
+      max_level: default::MAX_LEVEL,
+      base_size: default::BASE_SIZE,
+      fanout: default::FANOUT,
+      l0_max_file: default::L0_MAX_FILE,
+    }
+  }
+}
+
+impl From<&[Conf]> for Config {
+  fn from(conf_li: &[Conf]) -> Self {
+    let mut config = Self::default();
+    for &conf in conf_li {
+      match conf {
+        Conf::MaxLevel(v) => {
+          if v == Level::L0 {
+            log::warn!("MaxLevel must be L1-L6, keep {:?}", config.max_level);
+          } else {
+            config.max_level = v;
+          }
+        }
+        Conf::BaseSize(v) => config.base_size = v.max(1),
+        Conf::Fanout(v) => config.fanout = v.max(2),
+        Conf::L0MaxFile(v) => config.l0_max_file = v.max(1),
+      }
+    }
+    config
+  }
+}
+
+/// Default values
+/// 默认值
+pub mod default {
+  use jdb_base::sst::Level;
+
+  pub const MAX_LEVEL: Level = Level::L6;
+
+  /// Base level target size (256 MB)
+  /// 基础层的目标大小 (256 MB)
+  pub const BASE_SIZE: u64 = 256 * 1024 * 1024;
+
+  /// Size multiplier per level
+  /// 每一层的放大倍数
+  pub const FANOUT: u64 = 8;
+
+  /// L0 sublevel count to trigger compaction
+  /// L0 触发压缩的子层数
+  pub const L0_MAX_FILE: u64 = 4;
+}
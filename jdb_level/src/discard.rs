@@ -0,0 +1,22 @@
+//! Hand entries of removed SSTs to Discard
+//! 将被移除 SST 的条目交给 Discard
+
+use std::{borrow::Borrow, future::poll_fn, pin::pin};
+
+use futures_core::Stream;
+use jdb_base::{Discard, sst::Query};
+
+/// Report every entry of an SST dropped without rewrite,
+/// so its WAL space can be reclaimed
+/// 上报不经重写即被删除的 SST 的每个条目，以便回收其 WAL 空间
+pub async fn discard<'a, Q, D>(sst: &'a Q, discard: &mut D)
+where
+  Q: Query,
+  Q::Key<'a>: Borrow<[u8]>,
+  D: Discard,
+{
+  let mut stream = pin!(sst.iter());
+  while let Some((key, pos)) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+    discard.discard(key.borrow(), &pos);
+  }
+}
@@ -1,7 +1,46 @@
+use jdb_base::sst::Level;
+
+use crate::Id;
+
 /// JdbLevel Error
 /// JdbLevel 错误
-#[derive(thiserror::Error, Debug)]
-pub enum Error {}
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+  /// File overlaps a neighbour in a sorted level
+  /// 文件与有序层中的相邻文件重叠
+  #[error("sst {id} overlaps sst {other} in {level:?}")]
+  Overlap { level: Level, id: Id, other: Id },
+
+  /// File is not sorted by min key in a sorted level
+  /// 有序层中的文件未按最小键排序
+  #[error("sst {id} is out of order in {level:?}")]
+  Unsorted { level: Level, id: Id },
+
+  /// Removed id not found in level
+  /// 待移除的 ID 不在该层中
+  #[error("sst {id} not found in {level:?}")]
+  NotFound { level: Level, id: Id },
+
+  /// Id already present in levels
+  /// ID 已存在于层级中
+  #[error("sst {0} already exists")]
+  Exists(Id),
+
+  /// Min key greater than max key
+  /// 最小键大于最大键
+  #[error("sst {0} has min key > max key")]
+  Range(Id),
+
+  /// File lives deeper than the configured level shape allows
+  /// 文件所在层级超出配置的层级形态
+  #[error("sst {id} in {level:?} is beyond configured max level")]
+  Shape { level: Level, id: Id },
+
+  /// Level is out of range for a sorted (L1-L6) operation
+  /// 层级超出有序层（L1-L6）操作的范围
+  #[error("{0:?} is not a sorted level")]
+  Level(Level),
+}
 
 /// Result type for JdbLevel
 /// JdbLevel 的 Result 类型
@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+
+use jdb_base::{ckp::sst::Meta as SstMeta, sst::Level};
+use sorted_vec::SortedVec;
+
+use super::Levels;
+use crate::{
+  Id, Meta,
+  error::{Error, Result},
+  sink::Strategy,
+};
+
+impl<S> Levels<S> {
+  /// Get sorted level L1-L6
+  /// 获取有序层 L1-L6
+  #[inline]
+  pub fn sorted(&self, level: Level) -> Result<&SortedVec<Meta>> {
+    // Level 1-6 maps to index 0-5, L0 has no sorted run
+    // Level 1-6 对应索引 0-5，L0 没有有序层
+    (level as usize)
+      .checked_sub(1)
+      .and_then(|i| self.levels.get(i))
+      .ok_or(Error::Level(level))
+  }
+
+  /// Verify invariants: valid ranges, unique ids, L0 sublevels and
+  /// L1-L6 sorted by min key and disjoint (required by `overlap_for_sorted`)
+  /// 校验不变量：范围有效、ID 唯一、L0 子层与
+  /// L1-L6 按最小键排序且互不重叠（`overlap_for_sorted` 依赖于此）
+  pub fn check(&self) -> Result<()> {
+    let mut ids: HashSet<Id> = HashSet::new();
+    let mut seen = |m: &Meta| {
+      if m.min > m.max {
+        return Err(Error::Range(m.id));
+      }
+      if !ids.insert(m.id) {
+        return Err(Error::Exists(m.id));
+      }
+      Ok(())
+    };
+
+    // L0 sublevels and L1-L6 are all sorted runs
+    // L0 子层与 L1-L6 都是有序段
+    let runs =
+      self
+        .l0
+        .iter()
+        .map(|sub| (Level::L0, sub))
+        .chain(self.levels.iter().enumerate().map(|(i, li)| {
+          // SAFETY: i is in 0..6, i+1 is a valid Level L1-L6
+          // 安全：i 在 0..6，i+1 是有效的 Level L1-L6
+          let level: Level = unsafe { std::mem::transmute((i + 1) as u8) };
+          (level, li)
+        }));
+    for (level, li) in runs {
+      for m in li.iter() {
+        seen(m)?;
+      }
+      for w in li.windows(2) {
+        if w[1].min < w[0].min {
+          return Err(Error::Unsorted { level, id: w[1].id });
+        }
+        if w[1].min <= w[0].max {
+          return Err(Error::Overlap {
+            level,
+            id: w[1].id,
+            other: w[0].id,
+          });
+        }
+      }
+    }
+    Ok(())
+  }
+}
+
+impl<S: Strategy> Levels<S> {
+  /// Check added files: valid range, new id, no overlap in L1-L6
+  /// once `rm` is applied
+  /// 检查新增文件：范围有效、ID 为新、应用 `rm` 后在 L1-L6 中不重叠
+  pub(super) fn verify_add(&self, add: &[SstMeta], rm: &[(Level, Vec<Id>)]) -> Result<()> {
+    let is_rm = |level: Level, id: Id| rm.iter().any(|(l, ids)| *l == level && ids.contains(&id));
+
+    let max = self.sink.max_level();
+    let mut sorted: Vec<&SstMeta> = Vec::with_capacity(add.len());
+    for m in add {
+      let id = m.meta.id;
+      if m.sst.level > max {
+        return Err(Error::Shape {
+          level: m.sst.level,
+          id,
+        });
+      }
+      if m.meta.min > m.meta.max {
+        return Err(Error::Range(id));
+      }
+      if let Some(sst) = self.sink.get(id)
+        && !is_rm(sst.level, id)
+      {
+        return Err(Error::Exists(id));
+      }
+      let level = m.sst.level;
+      if level == Level::L0 {
+        continue;
+      }
+      let range = m.meta.min.as_ref()..=m.meta.max.as_ref();
+      if let Some(other) = self
+        .overlap(level, &range)?
+        .iter()
+        .find(|o| !is_rm(level, o.id))
+      {
+        return Err(Error::Overlap {
+          level,
+          id,
+          other: other.id,
+        });
+      }
+      sorted.push(m);
+    }
+
+    // Added files must not overlap each other
+    // 新增文件之间不能重叠
+    sorted.sort_unstable_by(|a, b| (a.sst.level, &a.meta.min).cmp(&(b.sst.level, &b.meta.min)));
+    for w in sorted.windows(2) {
+      if w[0].sst.level == w[1].sst.level && w[1].meta.min <= w[0].meta.max {
+        return Err(Error::Overlap {
+          level: w[1].sst.level,
+          id: w[1].meta.id,
+          other: w[0].meta.id,
+        });
+      }
+    }
+    Ok(())
+  }
+}
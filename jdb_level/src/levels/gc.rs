@@ -0,0 +1,72 @@
+use std::{collections::HashSet, fs, io, path::PathBuf};
+
+use ider::path::{decode, encode};
+use jdb_fs::{TMP, try_rm};
+
+use super::Levels;
+use crate::Id;
+
+/// Orphan files found by `Levels::gc`
+/// `Levels::gc` 找到的孤儿文件
+#[derive(Debug, Default)]
+pub struct Gc {
+  /// SSTs written but never committed by `Ckp::apply`
+  /// 已写入但未被 `Ckp::apply` 提交的 SST
+  pub sst: Vec<PathBuf>,
+  /// Leftover `AtomWrite` temp files
+  /// 残留的 `AtomWrite` 临时文件
+  pub tmp: Vec<PathBuf>,
+  /// Files that could not be removed (locked or busy)
+  /// 无法删除的文件（被锁定或占用）
+  pub fail: Vec<PathBuf>,
+}
+
+impl<S> Levels<S> {
+  /// Sweep SST dir for files not referenced by recovered levels,
+  /// call on open before any new SST is written.
+  /// With `dry_run` only report, never delete.
+  /// 清扫 SST 目录中未被恢复的层级引用的文件，应在写入新 SST 前于打开时调用。
+  /// `dry_run` 时仅报告，不删除。
+  pub fn gc(&self, dry_run: bool) -> io::Result<Gc> {
+    let live: HashSet<Id> = self
+      .l0
+      .iter()
+      .chain(&self.levels)
+      .flat_map(|li| li.iter())
+      .map(|m| m.id)
+      .collect();
+
+    let dir = self.lru.borrow().dir.clone();
+    let mut gc = Gc::default();
+    for entry in fs::read_dir(&dir)? {
+      let entry = entry?;
+      if !entry.file_type()?.is_file() {
+        continue;
+      }
+      let name = entry.file_name();
+      let Some(name) = name.to_str() else {
+        continue;
+      };
+      let li = if let Some(stem) = name.strip_suffix(TMP).and_then(|s| s.strip_suffix('.')) {
+        if decode(stem).is_none() {
+          continue;
+        }
+        &mut gc.tmp
+      } else {
+        // Only names that round-trip are ours, leave anything else alone
+        // 仅可往返编码的名称属于本库，其他文件不动
+        match decode(name) {
+          Some(id) if encode(id) == name && !live.contains(&id) => &mut gc.sst,
+          _ => continue,
+        }
+      };
+
+      let path = entry.path();
+      if !dry_run && !try_rm(&path) {
+        gc.fail.push(path.clone());
+      }
+      li.push(path);
+    }
+    Ok(gc)
+  }
+}
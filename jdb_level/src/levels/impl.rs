@@ -2,18 +2,43 @@ use jdb_base::sst::Level;
 use sorted_vec::SortedVec;
 
 use super::Levels;
-use crate::Meta;
+use crate::{
+  Id, Meta,
+  error::{Error, Result},
+};
 
-impl Levels {
+impl<S> Levels<S> {
   /// Push metadata to level
   /// 将元数据推入层级
+  /// Fails if range is invalid or overlaps a neighbour in L1-L6
+  /// 若范围无效或与 L1-L6 中的相邻文件重叠则失败
   #[inline]
-  pub(crate) fn push(&mut self, m: jdb_base::ckp::sst::Meta) {
+  pub(crate) fn push(&mut self, m: jdb_base::ckp::sst::Meta) -> Result<()> {
     // Score update handled in update trait, do not duplicate here!
     // 分数更新在 update trait 中处理，不要在这里重复！
+    let level = m.sst.level;
+    let id = m.meta.id;
+    if m.meta.min > m.meta.max {
+      return Err(Error::Range(id));
+    }
+    if level == Level::L0 {
+      if self.files(level).any(|x| x.id == id) {
+        return Err(Error::Exists(id));
+      }
+    } else if let Some(other) = self
+      .overlap(level, &(m.meta.min.as_ref()..=m.meta.max.as_ref()))?
+      .first()
+    {
+      return Err(Error::Overlap {
+        level,
+        id,
+        other: other.id,
+      });
+    }
+
     let meta = Meta::new(m.meta, self.lru.clone());
-    if m.sst.level == Level::L0 {
-      self.l0.push(meta);
+    if level == Level::L0 {
+      self.push_l0(meta);
     } else {
       // SAFETY: Level 1-6 maps to index 0-5
       // 安全：Level 1-6 对应索引 0-5
@@ -24,15 +49,31 @@ impl Levels {
           .push(meta);
       }
     }
+    Ok(())
   }
 
   /// Remove metadata by IDs (linear scan, small sets expected)
+  /// `moved` (sorted) are ids re-added to another level, their files are kept
   /// 通过 ID 移除元数据（线性扫描，预期小集合）
+  /// `moved`（已排序）是重新加入其他层的 ID，保留其文件
+  /// Fails without change if any id is not in the level
+  /// 若任一 ID 不在该层则失败且不做修改
   #[inline]
-  pub(crate) fn rm(&mut self, level: Level, ids: impl IntoIterator<Item = u64>) {
+  pub(crate) fn rm(
+    &mut self,
+    level: Level,
+    ids: impl IntoIterator<Item = u64>,
+    moved: &[u64],
+  ) -> Result<()> {
     let mut ids: Vec<u64> = ids.into_iter().collect();
     if ids.is_empty() {
-      return;
+      return Ok(());
+    }
+    if let Some(&id) = ids
+      .iter()
+      .find(|&&id| !self.files(level).any(|m| m.id == id))
+    {
+      return Err(Error::NotFound { level, id });
     }
     // Optimization: Sort IDs to allow O(log M) lookup instead of O(M)
     // 优化：对 ID 进行排序，允许 O(log M) 查找而不是 O(M)
@@ -43,7 +84,9 @@ impl Levels {
 
     let f = |m: &Meta| {
       if ids.binary_search(&m.id).is_ok() {
-        m.mark_rm();
+        if moved.binary_search(&m.id).is_err() {
+          m.mark_rm();
+        }
         false
       } else {
         true
@@ -51,7 +94,13 @@ impl Levels {
     };
 
     if level == Level::L0 {
-      self.l0.retain(f);
+      for sub in &mut self.l0 {
+        sub.retain(f);
+      }
+      self.l0.retain(|sub| !sub.is_empty());
+      for id in &ids {
+        self.l0_seq.remove(id);
+      }
     } else {
       // SAFETY: Level 1-6 maps to index 0-5
       // 安全：Level 1-6 对应索引 0-5
@@ -59,6 +108,50 @@ impl Levels {
         self.levels.get_unchecked_mut(level as usize - 1).retain(f);
       }
     }
+    Ok(())
+  }
+
+  /// Sequence position of an L0 file
+  /// L0 文件的序列位置
+  #[inline]
+  pub(crate) fn seq(&self, id: Id) -> Id {
+    self.l0_seq.get(&id).copied().unwrap_or(id)
+  }
+
+  /// Place L0 merge outputs at the position of their newest input `seq`,
+  /// then rebuild sublevels oldest first so newer files stay above
+  /// 将 L0 合并输出放在其最新输入 `seq` 的位置，
+  /// 再按从旧到新重建子层，使更新的文件保持在上方
+  pub(crate) fn place_l0(&mut self, ids: impl IntoIterator<Item = Id>, seq: Id) {
+    self.l0_seq.extend(ids.into_iter().map(|id| (id, seq)));
+    let mut li: Vec<Meta> = std::mem::take(&mut self.l0)
+      .into_iter()
+      .flat_map(SortedVec::into_vec)
+      .collect();
+    li.sort_unstable_by_key(|m| (self.seq(m.id), m.id));
+    for meta in li {
+      self.push_l0(meta);
+    }
+  }
+
+  /// Put newest L0 file right above the highest sublevel it overlaps
+  /// 将最新的 L0 文件放在与其重叠的最高子层之上
+  fn push_l0(&mut self, meta: Meta) {
+    let range = meta.min.as_ref()..=meta.max.as_ref();
+    let idx = self
+      .l0
+      .iter()
+      .rposition(|sub| {
+        !xrange::overlap_for_sorted(xrange::BorrowRange(&range, std::marker::PhantomData), sub)
+          .is_empty()
+      })
+      .map_or(0, |i| i + 1);
+    match self.l0.get_mut(idx) {
+      Some(sub) => {
+        sub.push(meta);
+      }
+      None => self.l0.push(SortedVec::from_unsorted(vec![meta])),
+    }
   }
 
   /// Push multiple metadatas (optimized)
@@ -80,11 +173,13 @@ impl Levels {
 
     let mut it = batch.into_iter();
 
-    // L0
-    if let Some(v) = it.next()
-      && !v.is_empty()
-    {
-      self.l0.extend(v);
+    // L0: older (smaller id) first, so newer files land above
+    // L0：较旧（id 较小）的先放入，使较新的文件位于其上
+    if let Some(mut v) = it.next() {
+      v.sort_unstable_by_key(|m| m.id);
+      for meta in v {
+        self.push_l0(meta);
+      }
     }
 
     // L1-L6
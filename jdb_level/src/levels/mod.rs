@@ -1,47 +1,152 @@
-use jdb_base::sst::Level;
+use std::collections::HashMap;
+
+use jdb_base::{sst::Level, stall::Signal};
+use jdb_fs::{
+  BufFile, RateLimit,
+  rate_limit::{self, Tune},
+};
 use sorted_vec::SortedVec;
 mod update;
-use crate::{LEVEL_LEN_MINUS_1, Meta, sink::Score};
+use crate::{
+  Id, LEVEL_LEN_MINUS_1, Meta,
+  error::{Error, Result},
+  sink::{Score, Strategy},
+  stats::Io,
+};
 
+mod check;
+mod gc;
+pub use gc::Gc;
 mod r#impl;
+mod plan;
+mod range;
 
 /// Levels managing SST metadata
 /// 管理 SST 元数据的层级
 #[derive(Debug)]
-pub struct Levels {
-  /// L0: append-only (by insertion time)
-  /// L0: 按插入时间追加
-  pub l0: Vec<Meta>,
+pub struct Levels<S = Score> {
+  /// L0 sublevels, oldest first: each sorted by min key and disjoint,
+  /// a file always sits above every sublevel it overlaps
+  /// L0 子层，最旧在前：每个子层按 min key 排序且互不重叠，
+  /// 文件总是位于所有与其重叠的子层之上
+  pub l0: Vec<SortedVec<Meta>>,
+  /// Sequence position of L0 files written by a merge into L0: they stand in for
+  /// their newest input, so flushes finished meanwhile stay above them.
+  /// Other L0 files use their id
+  /// 由合并写入 L0 的文件的序列位置：它们代替其最新的输入，
+  /// 使期间完成的刷盘保持在其之上。其他 L0 文件使用其 id
+  pub l0_seq: HashMap<Id, Id>,
   /// L1-L6: sorted by min key, disjoint
   /// L1-L6: 按 min key 排序，互不重叠
   pub levels: [SortedVec<Meta>; LEVEL_LEN_MINUS_1],
   pub lru: crate::Lru,
-  /// Scoring and GC state
-  /// 评分和 GC 状态
-  pub sink: Score,
+  /// Compaction strategy state
+  /// 压缩策略状态
+  pub sink: S,
+  /// Write stall signal, share with the memtable via `signal.clone()`
+  /// 写入停顿信号，通过 `signal.clone()` 与内存表共享
+  pub signal: Signal,
+  /// Cumulative flush and compaction bytes
+  /// 累计刷盘和压缩字节数
+  pub io: Io,
+  /// Newest WAL whose memtable is in SSTs, replay logs after it
+  /// 内存表已在 SST 中的最新 WAL，回放它之后的日志
+  pub wal_id: u64,
+  /// I/O limiter for flush and compaction output, its compaction rate
+  /// follows `Strategy::pending_bytes` after every applied op
+  /// 刷盘和压缩输出的 I/O 限速器，每次应用操作后其压缩速率跟随 `Strategy::pending_bytes`
+  pub rate_limit: Option<(RateLimit, Tune)>,
 }
 
 impl Levels {
-  /// Create new Levels
-  /// 创建新 Levels
+  /// Create new Levels with leveled compaction
+  /// 创建使用分层压缩的新 Levels
   #[inline]
   pub fn new(
     lru: crate::Lru,
     meta_iter: impl IntoIterator<Item = jdb_base::ckp::sst::Meta>,
-  ) -> Self {
+  ) -> Result<Self> {
+    Self::with_sink(lru, Score::default(), meta_iter)
+  }
+}
+
+impl<S: Strategy> Levels<S> {
+  /// Create new Levels with given compaction strategy,
+  /// recovered state is checked before use
+  /// 使用指定压缩策略创建新 Levels，恢复的状态在使用前会被校验
+  pub fn with_sink(
+    lru: crate::Lru,
+    mut sink: S,
+    meta_iter: impl IntoIterator<Item = jdb_base::ckp::sst::Meta>,
+  ) -> Result<Self> {
+    let meta_li: Vec<_> = meta_iter.into_iter().collect();
+    // On-disk shape must fit the configured one
+    // 磁盘上的形态必须符合配置
+    let max = sink.max_level();
+    if let Some(m) = meta_li.iter().find(|m| m.sst.level > max) {
+      return Err(Error::Shape {
+        level: m.sst.level,
+        id: m.meta.id,
+      });
+    }
+    for m in &meta_li {
+      sink.push(m.meta.id, m.sst);
+    }
+
     let mut levels = Self {
       l0: Vec::new(),
+      l0_seq: HashMap::new(),
       levels: Default::default(),
       lru,
-      sink: Score::new([]),
+      sink,
+      signal: Signal::default(),
+      io: Io::default(),
+      wal_id: 0,
+      rate_limit: None,
     };
 
-    levels.push_iter(meta_iter);
-    levels
+    levels.push_iter(meta_li);
+    levels.check()?;
+    levels.sink.l0_sublevels(levels.l0.len() as u64);
+    levels.signal.set(levels.sink.stall());
+    Ok(levels)
+  }
+}
+
+impl<S: Strategy> Levels<S> {
+  /// Share `limit` with flush and compaction output, tuning its compaction rate
+  /// 与刷盘和压缩输出共享 `limit`，并调整其压缩速率
+  pub fn set_rate_limit(&mut self, limit: RateLimit, tune: Tune) {
+    limit.tune(&tune, self.sink.pending_bytes());
+    self.rate_limit = Some((limit, tune));
   }
 
-  /// Get overlapping Metas in L0
-  /// 获取 L0 中重叠的 Meta
+  /// Re-tune compaction rate from pending bytes
+  /// 根据待压缩字节数重新调整压缩速率
+  #[inline]
+  pub(crate) fn tune(&mut self) {
+    if let Some((limit, tune)) = &self.rate_limit {
+      limit.tune(tune, self.sink.pending_bytes());
+    }
+  }
+}
+
+impl<S> Levels<S> {
+  /// Throttle an output file: `Io::Flush` for memtable SSTs, `Io::Compact` for merge output.
+  /// This is the only way the limiter reaches writes: this tree has no SST writer yet,
+  /// so whatever writes SSTs must open its files through here
+  /// 限制输出文件速率：内存表 SST 用 `Io::Flush`，合并输出用 `Io::Compact`。
+  /// 限速器只经由此处作用于写入：本仓库尚无 SST 写入器，
+  /// 因此写 SST 的代码必须经由此处打开其文件
+  #[inline]
+  pub fn limit(&self, file: &mut BufFile, io: rate_limit::Io) {
+    if let Some((limit, _)) = &self.rate_limit {
+      file.set_rate_limit(limit.clone(), io);
+    }
+  }
+
+  /// Get overlapping Metas in L0, newest sublevel first
+  /// 获取 L0 中重叠的 Meta，最新子层在前
   #[inline]
   pub fn overlap_l0<'a, K, R>(&'a self, range: &'a R) -> impl Iterator<Item = Meta> + 'a
   where
@@ -49,27 +154,26 @@ impl Levels {
     K: std::borrow::Borrow<[u8]>,
     R: std::ops::RangeBounds<K> + 'a,
   {
-    let r = xrange::BorrowRange(range, std::marker::PhantomData);
     self
       .l0
       .iter()
-      .filter(move |m| xrange::is_overlap(&r, &***m))
+      .rev()
+      .flat_map(move |sub| {
+        xrange::overlap_for_sorted(xrange::BorrowRange(range, std::marker::PhantomData), sub)
+      })
       .cloned()
   }
 
-  /// Get overlapping Metas in L1-L6
-  /// 获取 L1-L6 中重叠的 Meta
+  /// Get overlapping Metas in L1-L6, `Error::Level` for L0
+  /// 获取 L1-L6 中重叠的 Meta，L0 时返回 `Error::Level`
   #[inline]
-  pub fn overlap<'a, K, R>(&'a self, level: Level, range: &'a R) -> &'a [Meta]
+  pub fn overlap<'a, K, R>(&'a self, level: Level, range: &'a R) -> Result<&'a [Meta]>
   where
     K: ?Sized + Ord + 'a,
     K: std::borrow::Borrow<[u8]>,
     R: std::ops::RangeBounds<K> + 'a,
   {
     let r = xrange::BorrowRange(range, std::marker::PhantomData);
-    // SAFETY: level 1-6 maps to index 0-5
-    // 安全：level 1-6 对应索引 0-5
-    let v = unsafe { self.levels.get_unchecked(level as usize - 1) };
-    xrange::overlap_for_sorted(r, v)
+    Ok(xrange::overlap_for_sorted(r, self.sorted(level)?))
   }
 }
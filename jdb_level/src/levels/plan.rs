@@ -0,0 +1,228 @@
+use jdb_base::{
+  ckp::sst::Sst,
+  sst::{Level, Op},
+};
+use sorted_vec::SortedVec;
+
+use super::Levels;
+use crate::{
+  Id, Meta,
+  sink::{Sink, Strategy},
+  task::{Input, Merge, Rm, Task},
+};
+
+/// Min newest small L0 files to merge in place
+/// 原地合并所需的最少最新小 L0 文件数
+const INTRA_L0_MIN_FILES: usize = 4;
+
+/// Max total size of an intra-L0 merge (64 MB)
+/// 单次 L0 内部合并的最大总大小 (64 MB)
+const INTRA_L0_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Check that files are pairwise disjoint
+/// 检查文件两两互不重叠
+fn is_disjoint(meta_li: &[Meta]) -> bool {
+  let mut li: Vec<&Meta> = meta_li.iter().collect();
+  li.sort_unstable();
+  li.windows(2).all(|w| w[0].max < w[1].min)
+}
+
+impl<S> Levels<S> {
+  /// Get all files of a level, L0 newest sublevel first
+  /// 获取某层的全部文件，L0 最新子层在前
+  #[inline]
+  pub fn files(&self, level: Level) -> impl Iterator<Item = &Meta> + Clone {
+    let (l0, li): (&[SortedVec<Meta>], &[Meta]) = if level == Level::L0 {
+      (&self.l0, &[])
+    } else {
+      // SAFETY: Level 1-6 maps to index 0-5
+      // 安全：Level 1-6 对应索引 0-5
+      (&[], unsafe {
+        self.levels.get_unchecked(level as usize - 1)
+      })
+    };
+    l0.iter().rev().flat_map(|sub| sub.iter()).chain(li)
+  }
+
+  /// Get files of a level by ids
+  /// 按 ID 获取某层的文件
+  #[inline]
+  fn pick(&self, level: Level, ids: &[Id]) -> Vec<Meta> {
+    self
+      .files(level)
+      .filter(|m| ids.contains(&m.id))
+      .cloned()
+      .collect()
+  }
+
+  /// Get files in `to` overlapping the key span of `meta_li`
+  /// 获取 `to` 中与 `meta_li` 键跨度重叠的文件
+  fn overlap_span(&self, to: Level, meta_li: &[Meta]) -> Vec<Meta> {
+    let (Some(min), Some(max)) = (
+      meta_li.iter().map(|m| &m.min).min(),
+      meta_li.iter().map(|m| &m.max).max(),
+    ) else {
+      return Vec::new();
+    };
+    let range = min.as_ref()..=max.as_ref();
+    match self.overlap(to, &range) {
+      Ok(li) => li.to_vec(),
+      Err(_) => self.overlap_l0(&range).collect(),
+    }
+  }
+
+  /// Check if no level below `to` holds keys in the span of `input`.
+  /// Output into L0 is never bottom, older sublevels may lie beneath
+  /// 检查 `to` 下方的层是否都没有 `input` 键跨度内的键。
+  /// 输出到 L0 时永远不是最底层，下方可能有更老的子层
+  pub(super) fn is_bottom(&self, to: Level, input: &Input) -> bool {
+    if to == Level::L0 {
+      return false;
+    }
+    let all: Vec<Meta> = input.iter().flat_map(|(_, m)| m.iter().cloned()).collect();
+    let mut level = to;
+    while let Some(next) = level.next() {
+      if !self.overlap_span(next, &all).is_empty() {
+        return false;
+      }
+      level = next;
+    }
+    true
+  }
+}
+
+impl<S: Strategy> Levels<S> {
+  /// Build metadata-only move of `meta_li` from `from` to `to`
+  /// 构建将 `meta_li` 从 `from` 移动到 `to` 的仅元数据操作
+  fn mv(&self, from: Level, to: Level, meta_li: &[Meta]) -> Option<Task> {
+    let mut add = Vec::with_capacity(meta_li.len());
+    for m in meta_li {
+      let sst = self.sink.get(m.id)?;
+      add.push(jdb_base::ckp::sst::Meta {
+        sst: Sst { level: to, ..*sst },
+        meta: (**m).clone(),
+      });
+    }
+    Some(Task::Move(Op::Compact {
+      add,
+      rm: vec![(from, meta_li.iter().map(|m| m.id).collect())],
+    }))
+  }
+
+  /// L0 files newest first by sequence position
+  /// 按序列位置排列的 L0 文件，最新在前
+  fn l0_newest(&self) -> Vec<&Meta> {
+    let mut l0: Vec<&Meta> = self.files(Level::L0).collect();
+    l0.sort_unstable_by_key(|m| std::cmp::Reverse((self.seq(m.id), m.id)));
+    l0
+  }
+
+  /// Strategies see L0 in id order, but merge outputs sit at their sequence position:
+  /// take the same window of files in sequence order
+  /// 策略按 id 顺序看待 L0，但合并输出位于其序列位置：按序列顺序取相同窗口的文件
+  fn pick_l0(&self, ids: &[Id]) -> Vec<Meta> {
+    let by_seq = self.l0_newest();
+    let mut by_id = by_seq.clone();
+    by_id.sort_unstable_by_key(|m| std::cmp::Reverse(m.id));
+    let pos: Vec<usize> = by_id
+      .iter()
+      .enumerate()
+      .filter(|(_, m)| ids.contains(&m.id))
+      .map(|(i, _)| i)
+      .collect();
+    match (pos.first(), pos.last()) {
+      (Some(&start), Some(&end)) if end - start + 1 == pos.len() => {
+        by_seq[start..=end].iter().map(|&m| m.clone()).collect()
+      }
+      _ => self.pick(Level::L0, ids),
+    }
+  }
+
+  /// Pick newest L0 files while their total stays small.
+  /// Only a newest contiguous run may merge in place: the output takes
+  /// the place of its newest input and must shadow every older L0 file.
+  /// 在总大小较小时选取最新的 L0 文件。
+  /// 只有最新的连续文件可以原地合并：输出占据其最新输入的位置，必须覆盖所有更旧的 L0 文件。
+  fn intra_l0(&self) -> Option<Vec<Meta>> {
+    let mut size = 0u64;
+    let mut picked = Vec::new();
+    for m in self.l0_newest() {
+      let s = self.sink.get(m.id)?.size;
+      if size.saturating_add(s) > INTRA_L0_MAX_SIZE {
+        break;
+      }
+      size += s;
+      picked.push(m.clone());
+    }
+    (picked.len() >= INTRA_L0_MIN_FILES).then_some(picked)
+  }
+
+  /// Plan compaction task for a Sink, None if its files are gone
+  /// 为 Sink 规划压缩任务，文件已不存在时返回 None
+  pub fn plan(&self, sink: Sink) -> Option<Task> {
+    let (to, input) = match sink {
+      Sink::L0(to) => {
+        let l0: Vec<Meta> = self.files(Level::L0).cloned().collect();
+        if l0.is_empty() {
+          return None;
+        }
+        let below = self.overlap_span(to, &l0);
+        // Nothing to merge with: move instead of rewrite
+        // 无需合并：移动而非重写
+        if below.is_empty()
+          && is_disjoint(&l0)
+          && let Some(task) = self.mv(Level::L0, to, &l0)
+        {
+          return Some(task);
+        }
+        (to, vec![(Level::L0, l0), (to, below)])
+      }
+      Sink::IntraL0(to) => match self.intra_l0() {
+        Some(l0) => (Level::L0, vec![(Level::L0, l0)]),
+        None => return self.plan(Sink::L0(to)),
+      },
+      Sink::L1Plus { from, to, id } => {
+        let meta_li = self.pick(from, &[id]);
+        if meta_li.is_empty() {
+          return None;
+        }
+        let below = self.overlap_span(to, &meta_li);
+        if below.is_empty()
+          && let Some(task) = self.mv(from, to, &meta_li)
+        {
+          return Some(task);
+        }
+        (to, vec![(from, meta_li), (to, below)])
+      }
+      Sink::Tier { l0, levels, to } => {
+        let mut input: Input = Vec::with_capacity(levels.len() + 2);
+        input.push((Level::L0, self.pick_l0(&l0)));
+        for level in levels {
+          input.push((level, self.files(level).cloned().collect()));
+        }
+        // Output into L0 only merges the picked files
+        // 输出到 L0 时只合并选中的文件
+        if to != Level::L0 && input.iter().all(|(level, _)| *level != to) {
+          let all: Vec<Meta> = input.iter().flat_map(|(_, m)| m.iter().cloned()).collect();
+          input.push((to, self.overlap_span(to, &all)));
+        }
+        if input.iter().all(|(_, m)| m.is_empty()) {
+          return None;
+        }
+        (to, input)
+      }
+      Sink::Drop(rm) => {
+        let input: Input = rm
+          .into_iter()
+          .map(|(level, ids)| (level, self.pick(level, &ids)))
+          .collect();
+        if input.iter().all(|(_, m)| m.is_empty()) {
+          return None;
+        }
+        return Some(Task::Rm(Rm { input }));
+      }
+    };
+    let bottom = self.is_bottom(to, &input);
+    Some(Task::Merge(Merge { to, input, bottom }))
+  }
+}
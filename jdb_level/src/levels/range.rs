@@ -0,0 +1,150 @@
+use std::ops::{Bound, RangeBounds};
+
+use jdb_base::sst::Level;
+
+use super::Levels;
+use crate::{
+  Meta,
+  error::{Error, Result},
+  task::{Input, Merge, Rm, Task},
+};
+
+/// Owned key bound
+/// 拥有所有权的键边界
+type Key = Bound<Box<[u8]>>;
+
+#[inline]
+fn owned(bound: Bound<&[u8]>) -> Key {
+  match bound {
+    Bound::Included(k) => Bound::Included(k.into()),
+    Bound::Excluded(k) => Bound::Excluded(k.into()),
+    Bound::Unbounded => Bound::Unbounded,
+  }
+}
+
+#[inline]
+fn borrowed(bound: &Key) -> Bound<&[u8]> {
+  match bound {
+    Bound::Included(k) => Bound::Included(k),
+    Bound::Excluded(k) => Bound::Excluded(k),
+    Bound::Unbounded => Bound::Unbounded,
+  }
+}
+
+/// Widen span to cover files
+/// 扩展跨度以覆盖文件
+fn widen(span: &mut (Key, Key), meta_li: &[Meta]) {
+  for m in meta_li {
+    match &span.0 {
+      Bound::Included(k) | Bound::Excluded(k) if m.min.as_ref() <= k.as_ref() => {
+        span.0 = Bound::Included(m.min.clone());
+      }
+      _ => {}
+    }
+    match &span.1 {
+      Bound::Included(k) | Bound::Excluded(k) if m.max.as_ref() >= k.as_ref() => {
+        span.1 = Bound::Included(m.max.clone());
+      }
+      _ => {}
+    }
+  }
+}
+
+impl<S> Levels<S> {
+  /// Plan merge of every file overlapping `range` from L0 down to `to`,
+  /// widening the span level by level so no overlapping file is left behind.
+  /// Files below `to` hold older data and are left in place.
+  /// 规划将所有与 `range` 重叠的文件从 L0 合并到 `to`，
+  /// 逐层扩展跨度以免遗漏重叠文件。`to` 之下的文件数据更旧，保持不动。
+  pub fn compact_range<R: RangeBounds<[u8]>>(&self, range: &R, to: Level) -> Result<Option<Task>> {
+    if to == Level::L0 {
+      return Err(Error::Level(to));
+    }
+
+    let mut span = (owned(range.start_bound()), owned(range.end_bound()));
+    // A wider lower file can pull in more L0 files, which can widen it again,
+    // so rescan every level until the span stops growing
+    // 更宽的下层文件可能引入更多 L0 文件，进而再次扩展跨度，
+    // 因此反复扫描各层直到跨度不再增长
+    let input = loop {
+      let before = span.clone();
+      let mut input: Input = Vec::new();
+      let r = (borrowed(&span.0), borrowed(&span.1));
+      let l0: Vec<Meta> = self.overlap_l0::<[u8], _>(&r).collect();
+      widen(&mut span, &l0);
+      if !l0.is_empty() {
+        input.push((Level::L0, l0));
+      }
+
+      let mut level = Level::L1;
+      loop {
+        let r = (borrowed(&span.0), borrowed(&span.1));
+        let meta_li = self.overlap::<[u8], _>(level, &r)?.to_vec();
+        if !meta_li.is_empty() {
+          widen(&mut span, &meta_li);
+          input.push((level, meta_li));
+        }
+        if level == to {
+          break;
+        }
+        // to is L1-L6, so next() is always Some before reaching it
+        // to 为 L1-L6，到达前 next() 总为 Some
+        let Some(next) = level.next() else {
+          break;
+        };
+        level = next;
+      }
+
+      if span == before {
+        break input;
+      }
+    };
+
+    if input.is_empty() {
+      return Ok(None);
+    }
+    let bottom = self.is_bottom(to, &input);
+    Ok(Some(Task::Merge(Merge { to, input, bottom })))
+  }
+
+  /// Plan removal without rewrite of every file fully inside `range`,
+  /// partially covered files are left for a range-tombstone compaction.
+  /// Entries of removed files should be handed to Discard (see `crate::discard`)
+  /// 规划不经重写移除所有完全位于 `range` 内的文件，
+  /// 部分覆盖的文件留给范围墓碑压缩处理。
+  /// 被移除文件的条目应交给 Discard（见 `crate::discard`）
+  pub fn delete_files_in_range<R: RangeBounds<[u8]>>(&self, range: &R) -> Option<Task> {
+    let inside = |m: &&Meta| range.contains::<[u8]>(&m.min) && range.contains::<[u8]>(&m.max);
+
+    let mut input: Input = Vec::new();
+    let l0: Vec<Meta> = self.files(Level::L0).filter(inside).cloned().collect();
+    if !l0.is_empty() {
+      input.push((Level::L0, l0));
+    }
+    let mut level = Level::L1;
+    loop {
+      // Walks L1-L6 only, which always have a sorted run
+      // 只遍历 L1-L6，它们总有有序层
+      let meta_li: Vec<Meta> = self
+        .overlap::<[u8], _>(level, range)
+        .unwrap_or_default()
+        .iter()
+        .filter(inside)
+        .cloned()
+        .collect();
+      if !meta_li.is_empty() {
+        input.push((level, meta_li));
+      }
+      let Some(next) = level.next() else {
+        break;
+      };
+      level = next;
+    }
+
+    if input.is_empty() {
+      None
+    } else {
+      Some(Task::Rm(Rm { input }))
+    }
+  }
+}
@@ -1,32 +1,133 @@
-use jdb_base::ckp::sst::ckp::{Levels, Op};
+use jdb_base::{
+  ckp::sst::{
+    Meta as SstMeta,
+    ckp::{Levels, Op},
+  },
+  sst::Level,
+};
+
+use crate::{error::Error, sink::Strategy};
+
+impl<S: Strategy> Levels for crate::Levels<S> {
+  type Error = Error;
 
-impl Levels for crate::Levels {
   #[inline]
-  fn update(&mut self, op: Op) {
+  fn verify(&self, op: &Op) -> Result<(), Error> {
     match op {
-      Op::Mem2Sst { meta } => {
-        // Update score and levels with new SST
-        // 使用新 SST 更新分数和层级
-        self.sink.push(meta.meta.id, meta.sst);
-        self.push(meta);
+      Op::Mem2Sst { meta } | Op::Mem2SstWal { meta, .. } => {
+        self.verify_add(std::slice::from_ref(meta), &[])
       }
       Op::Compact { add, rm } => {
-        // Update score lazily with iterators
-        // 使用迭代器延迟更新分数
-        self.sink.sink(
-          add.iter().map(|m| (m.meta.id, m.sst)),
-          rm.iter().map(|(level, ids)| (*level, ids.as_slice())),
-        );
+        self.verify_rm(rm)?;
+        self.verify_add(add, rm)
+      }
+    }
+  }
+
+  #[inline]
+  fn update(&mut self, op: Op) -> Result<(), Error> {
+    match op {
+      // Manifests older than WAL ids carry none
+      // 早于 WAL id 的清单不携带该字段
+      Op::Mem2Sst { meta } => self.mem2sst(meta, 0)?,
+      Op::Mem2SstWal { meta, wal_id } => self.mem2sst(meta, wal_id)?,
+      Op::Compact { add, rm } => {
+        // Ids both removed and added are trivial moves, keep their files
+        // 同时被移除和添加的 ID 是平凡移动，保留其文件
+        let mut moved: Vec<u64> = add.iter().map(|m| m.meta.id).collect();
+        moved.sort_unstable();
+
+        // Count rewrite I/O: moves copy nothing, drops read nothing
+        // 统计重写 I/O：移动不复制，删除不读取
+        let is_rm = |id: u64| rm.iter().any(|(_, ids)| ids.contains(&id));
+        let write: u64 = add
+          .iter()
+          .filter(|m| !is_rm(m.meta.id))
+          .map(|m| m.sst.size)
+          .sum();
+        let read: u64 = if write > 0 {
+          rm.iter()
+            .flat_map(|(_, ids)| ids)
+            .filter(|id| moved.binary_search(id).is_err())
+            .filter_map(|&id| self.sink.get(id))
+            .map(|sst| sst.size)
+            .sum()
+        } else {
+          0
+        };
+
+        // Outputs into L0 take the place of their newest L0 input
+        // 写入 L0 的输出占据其最新 L0 输入的位置
+        let seq = rm
+          .iter()
+          .filter(|(level, _)| *level == Level::L0)
+          .flat_map(|(_, ids)| ids)
+          .map(|&id| self.seq(id))
+          .max();
+        let l0_add: Vec<u64> = add
+          .iter()
+          .filter(|m| m.sst.level == Level::L0 && !is_rm(m.meta.id))
+          .map(|m| m.meta.id)
+          .collect();
+
+        // Check every removal before touching anything, so a failure changes no state
+        // 修改任何状态前先检查所有移除项，使失败时状态不变
+        self.verify_rm(&rm)?;
+        let sink_add: Vec<_> = add.iter().map(|m| (m.meta.id, m.sst)).collect();
 
         // Update levels state
         // 更新层级状态
-        for (level, ids) in rm {
-          self.rm(level, ids);
+        for (level, ids) in &rm {
+          self.rm(*level, ids.iter().copied(), &moved)?;
         }
         if !add.is_empty() {
           self.push_iter(add);
         }
+        if let Some(seq) = seq
+          && !l0_add.is_empty()
+        {
+          self.place_l0(l0_add, seq);
+        }
+
+        // Update score only once the levels changed
+        // 层级变更后才更新分数
+        self.sink.sink(
+          sink_add,
+          rm.iter().map(|(level, ids)| (*level, ids.as_slice())),
+        );
+        self.io.read += read;
+        self.io.write += write;
+      }
+    }
+    self.sink.l0_sublevels(self.l0.len() as u64);
+    self.signal.set(self.sink.stall());
+    self.tune();
+    Ok(())
+  }
+}
+
+impl<S: Strategy> crate::Levels<S> {
+  #[inline]
+  fn mem2sst(&mut self, meta: SstMeta, wal_id: u64) -> Result<(), Error> {
+    // Update levels with new SST first, score only once it is in
+    // 先用新 SST 更新层级，加入成功后才更新分数
+    let (id, sst) = (meta.meta.id, meta.sst);
+    self.push(meta)?;
+    self.wal_id = self.wal_id.max(wal_id);
+    self.io.flush += sst.size;
+    self.sink.push(id, sst);
+    Ok(())
+  }
+
+  /// Every id to remove must exist at its level
+  /// 每个待移除的 ID 都必须存在于其层级
+  fn verify_rm(&self, rm: &[(Level, Vec<u64>)]) -> Result<(), Error> {
+    for (level, ids) in rm {
+      let files = self.files(*level);
+      if let Some(&id) = ids.iter().find(|&&id| !files.clone().any(|m| m.id == id)) {
+        return Err(Error::NotFound { level: *level, id });
       }
     }
+    Ok(())
   }
 }
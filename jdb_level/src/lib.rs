@@ -1,13 +1,20 @@
+pub mod conf;
+mod discard;
 pub mod error;
 mod levels;
 mod meta;
 pub mod sink;
+pub mod stats;
+pub mod task;
 
 use std::{cell::RefCell, rc::Rc};
 
+pub use discard::discard;
 use file_lru::FileLru;
-pub use levels::Levels;
+pub use levels::{Gc, Levels};
 pub use meta::Meta;
+pub use stats::Stats;
+pub use task::Task;
 
 /// Shared FileLru type alias
 /// 共享 FileLru 类型别名
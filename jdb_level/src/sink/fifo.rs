@@ -0,0 +1,105 @@
+//! FIFO compaction for time-series tables
+//! 面向时序表的 FIFO 压缩
+//!
+//! Files are never rewritten: the oldest ones (by id, i.e. creation order)
+//! are dropped once total size or age passes the limit.
+//! 文件从不重写：总大小或存活时间超限时，删除最旧的文件（按 id，即创建顺序）。
+
+use std::{
+  collections::BTreeMap,
+  time::{SystemTime, UNIX_EPOCH},
+};
+
+use ider::id_to_ts;
+use jdb_base::{ckp::sst::Sst, sst::Level};
+
+use super::{Sink, Strategy};
+use crate::Id;
+
+/// FIFO compaction state
+/// FIFO 压缩状态
+#[derive(Debug, Default)]
+pub struct Fifo {
+  /// Max total virtual size, oldest files dropped beyond it
+  /// 最大虚拟总大小，超出时删除最旧的文件
+  pub max_size: u64,
+  /// Time to live in seconds, 0 means never expire
+  /// 存活时间（秒），0 表示永不过期
+  pub ttl: u64,
+  /// Total virtual size of the files
+  /// 文件的虚拟总大小
+  pub total_size: u64,
+  /// Files sorted by id (creation order)
+  /// 按 id（创建顺序）排序的文件
+  pub files: BTreeMap<Id, Sst>,
+}
+
+impl Fifo {
+  #[inline]
+  pub fn new(max_size: u64, ttl: u64) -> Self {
+    Self {
+      max_size,
+      ttl,
+      ..Default::default()
+    }
+  }
+}
+
+impl Iterator for Fifo {
+  type Item = Sink;
+
+  /// Get oldest files to drop
+  /// 获取待删除的最旧文件
+  fn next(&mut self) -> Option<Self::Item> {
+    let now = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map_or(0, |d| d.as_secs());
+
+    let mut size = self.total_size;
+    let mut rm: Vec<(Level, Vec<Id>)> = Vec::new();
+    for (&id, sst) in &self.files {
+      // Ids grow with time: once one file is young enough, all newer ones are too
+      // Id 随时间增长：一旦某个文件未过期，更新的文件也不会过期
+      let expired = self.ttl > 0 && now.saturating_sub(id_to_ts(id)) > self.ttl;
+      if size <= self.max_size && !expired {
+        break;
+      }
+      size = size.saturating_sub(sst.virtual_size());
+      match rm.iter_mut().find(|(level, _)| *level == sst.level) {
+        Some((_, ids)) => ids.push(id),
+        None => rm.push((sst.level, vec![id])),
+      }
+    }
+
+    if rm.is_empty() {
+      None
+    } else {
+      Some(Sink::Drop(rm))
+    }
+  }
+}
+
+impl Strategy for Fifo {
+  #[inline]
+  fn push(&mut self, id: Id, sst: Sst) {
+    self.total_size = self.total_size.saturating_add(sst.virtual_size());
+    self.files.insert(id, sst);
+  }
+
+  #[inline]
+  fn get(&self, id: Id) -> Option<&Sst> {
+    self.files.get(&id)
+  }
+
+  #[inline]
+  fn rm<'a>(&mut self, iter: impl IntoIterator<Item = (Level, &'a [Id])>) {
+    for (level, ids) in iter {
+      for id in ids {
+        if let Some(sst) = self.files.remove(id) {
+          debug_assert_eq!(sst.level, level);
+          self.total_size = self.total_size.saturating_sub(sst.virtual_size());
+        }
+      }
+    }
+  }
+}
@@ -1,32 +1,26 @@
 use jdb_base::sst::Level;
 
 use super::LevelSize;
-use crate::LEVEL_LEN_MINUS_1;
+use crate::{LEVEL_LEN_MINUS_1, conf::Config};
 
-/// Base level target size (256 MB)
-/// 基础层的目标大小 (256 MB)
-const BASE_SIZE: u64 = 256 * 1024 * 1024;
-
-/// Size multiplier per level
-/// 每一层的放大倍数
-const SCALE: u64 = 8;
-
-/// Calculate target size for each level based on total data size
+/// Calculate target size for each level based on total data size,
+/// the deepest configured level holds all data, levels below it stay empty
 /// Returns (LevelSize, base_level)
-/// 根据总数据大小计算每层目标大小
+/// 根据总数据大小计算每层目标大小，配置的最深层容纳全部数据，其下各层保持为空
 /// 返回 (LevelSize, base_level)
 #[inline]
-pub fn level_target_size(total: u64) -> (LevelSize, Level) {
+pub fn level_target_size(total: u64, conf: &Config) -> (LevelSize, Level) {
   let mut li = [0u64; LEVEL_LEN_MINUS_1];
-  let mut idx = LEVEL_LEN_MINUS_1 - 1;
+  let bottom = conf.max_level as usize - 1;
+  let mut idx = bottom;
   let mut target = total;
 
-  for i in (0..LEVEL_LEN_MINUS_1).rev() {
-    // Optimization: If target < BASE_SIZE and not the last level (L6), stop early.
-    // This ensures Base Level >= BASE_SIZE to buffer L0 data effectively.
-    // 优化：如果目标小于 BASE_SIZE 且不是最后一层（L6），提早停止。
-    // 这确保留基础层 >= BASE_SIZE，以有效缓冲 L0 数据。
-    if target < BASE_SIZE && i < LEVEL_LEN_MINUS_1 - 1 {
+  for i in (0..=bottom).rev() {
+    // Optimization: If target < base_size and not the bottom level, stop early.
+    // This ensures Base Level >= base_size to buffer L0 data effectively.
+    // 优化：如果目标小于 base_size 且不是最底层，提早停止。
+    // 这确保留基础层 >= base_size，以有效缓冲 L0 数据。
+    if target < conf.base_size && i < bottom {
       break;
     }
 
@@ -37,7 +31,7 @@ pub fn level_target_size(total: u64) -> (LevelSize, Level) {
     }
     idx = i;
 
-    target /= SCALE;
+    target /= conf.fanout;
   }
 
   // Convert index to Level: 0..6 -> L1..L6
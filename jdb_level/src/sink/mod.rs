@@ -1,9 +1,15 @@
+mod fifo;
 mod level_size;
 mod score;
+mod strategy;
+mod tiered;
 
+pub use fifo::Fifo;
 use jdb_base::sst::Level;
 use level_size::{find_base_level, level_target_size};
-pub use score::Score;
+pub use score::{SCALE, Score};
+pub use strategy::Strategy;
+pub use tiered::{Run, Tiered};
 
 use crate::{Id, LEVEL_LEN_MINUS_1};
 
@@ -14,7 +20,26 @@ pub type LevelSize = [u64; LEVEL_LEN_MINUS_1];
 // 下沉的目标层级
 pub type ToLevel = Level;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Sink {
   L0(ToLevel),
-  L1Plus { from: Level, to: ToLevel, id: Id },
+  /// Merge newest small L0 files into one L0 file while the base level is busy,
+  /// falls back to `L0` when there are too few of them
+  /// 基础层繁忙时将最新的小 L0 文件合并为一个 L0 文件，数量不足时回退为 `L0`
+  IntraL0(ToLevel),
+  L1Plus {
+    from: Level,
+    to: ToLevel,
+    id: Id,
+  },
+  /// Merge sorted runs (tiered): L0 files and whole levels
+  /// 合并有序段（分级）：L0 文件和整层
+  Tier {
+    l0: Vec<Id>,
+    levels: Vec<Level>,
+    to: ToLevel,
+  },
+  /// Drop files without rewrite (FIFO)
+  /// 不重写直接删除文件（FIFO）
+  Drop(Vec<(Level, Vec<Id>)>),
 }
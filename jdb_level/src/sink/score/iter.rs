@@ -20,6 +20,16 @@ impl Iterator for Score {
     let from: Level = unsafe { std::mem::transmute(idx as u8) };
 
     if from == Level::L0 {
+      // Base level still over target: reduce L0 read amplification in place
+      // 基础层仍超出目标：原地降低 L0 读放大
+      let base = self
+        .score
+        .get(self.base_level as usize)
+        .copied()
+        .unwrap_or(0);
+      if base >= SCALE as LevelScore {
+        return Some(Sink::IntraL0(self.base_level));
+      }
       return Some(Sink::L0(self.base_level));
     }
 
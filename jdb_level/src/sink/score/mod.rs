@@ -3,30 +3,47 @@ mod r#pub;
 
 use std::collections::HashMap;
 
-use jdb_base::{ckp::sst::Sst, sst::Level};
+use jdb_base::{
+  ckp::sst::Sst,
+  sst::Level,
+  stall::{Reason, Stall},
+};
 
 use super::{LevelSize, find_base_level, level_target_size};
-use crate::{Id, LEVEL_LEN_MINUS_1};
+use crate::{Id, LEVEL_LEN_MINUS_1, conf::Config};
 
 /// Score scale: 128 represents 100%
 /// 评分基准：128 代表 100%
 pub const SCALE: u64 = 128;
 
-/// L0 max file count to trigger compaction
-/// L0 触发压缩的最大文件数
-pub const L0_MAX_FILE_NUMBER: u64 = 4;
+/// L0 sublevel count to delay writes
+/// 延迟写入的 L0 子层数
+pub const L0_SLOWDOWN: u64 = 20;
+
+/// L0 sublevel count to stop writes
+/// 停止写入的 L0 子层数
+pub const L0_STOP: u64 = 36;
+
+/// Pending compaction bytes to delay writes (64 GB)
+/// 延迟写入的待压缩字节数 (64 GB)
+pub const PENDING_SLOWDOWN: u64 = 64 << 30;
+
+/// Pending compaction bytes to stop writes (256 GB)
+/// 停止写入的待压缩字节数 (256 GB)
+pub const PENDING_STOP: u64 = 256 << 30;
 
 pub type LevelScore = u32;
 pub type FileScore = u32;
 
-/// Calculate sink score for L0
-/// 计算 L0 的下沉得分
+/// Calculate sink score for L0 from its sublevel count, `max` sublevels means 100%
+/// 按子层数计算 L0 的下沉得分，`max` 个子层代表 100%
 #[inline]
-pub fn l0(count: u64) -> LevelScore {
-  if count > L0_MAX_FILE_NUMBER * 2 {
+pub fn l0(count: u64, max: u64) -> LevelScore {
+  let max = max.max(1);
+  if count > max.saturating_mul(2) {
     return LevelScore::MAX;
   }
-  (count.saturating_mul(SCALE) / L0_MAX_FILE_NUMBER) as LevelScore
+  (count.saturating_mul(SCALE) / max) as LevelScore
 }
 
 /// Calculate sink score for L1+
@@ -66,6 +83,10 @@ pub fn file_score(sst: &Sst) -> FileScore {
 pub struct Score {
   pub total_size: u64,
   pub l0_cnt: u64,
+  /// L0 sublevels, reported by Levels; overlapping files are what slow reads
+  /// L0 子层数，由 Levels 上报；拖慢读取的是互相重叠的文件
+  pub l0_sub: u64,
+  pub l0_size: u64,
   pub level_size: LevelSize,
   pub score: [LevelScore; LEVEL_LEN_MINUS_1],
   pub level_target_size: LevelSize,
@@ -78,6 +99,9 @@ pub struct Score {
   /// Id to Sst mapping
   /// ID 到 Sst 的映射
   pub id_sst: HashMap<Id, Sst>,
+  /// Level shape
+  /// 层级形态
+  pub conf: Config,
   /// Recomputation needed
   /// 需要重新计算
   dirty: bool,
@@ -103,6 +127,7 @@ impl Score {
 
     if sst.level == Level::L0 {
       self.l0_cnt = self.l0_cnt.saturating_add(1);
+      self.l0_size = self.l0_size.saturating_add(size);
     } else {
       let idx = sst.level as usize - 1;
       // SAFETY: Level L1-L6 maps to index 0-5
@@ -129,6 +154,7 @@ impl Score {
 
     if sst.level == Level::L0 {
       self.l0_cnt = self.l0_cnt.saturating_sub(1);
+      self.l0_size = self.l0_size.saturating_sub(size);
     } else {
       let idx = sst.level as usize - 1;
       // SAFETY: Level L1-L6 maps to index 0-5
@@ -145,27 +171,69 @@ impl Score {
     }
   }
 
+  /// Bytes over target: all of L0 once it triggers, plus excess of levels above the bottom
+  /// 超出目标的字节数：触发后的全部 L0，加上最底层之上各层的超出部分
+  pub(super) fn pending(&mut self) -> u64 {
+    self.update();
+    let mut pending = if self.l0_sub >= self.conf.l0_max_file {
+      self.l0_size
+    } else {
+      0
+    };
+    for (&actual, &target) in self
+      .level_size
+      .iter()
+      .zip(&self.level_target_size)
+      .take(self.conf.max_level as usize - 1)
+    {
+      pending = pending.saturating_add(actual.saturating_sub(target));
+    }
+    pending
+  }
+
+  /// Stall from L0 sublevel count and pending bytes, stop wins over delay
+  /// 根据 L0 子层数和待压缩字节数决定停顿，停止优先于延迟
+  pub(super) fn write_stall(&mut self) -> Stall {
+    let pending = self.pending();
+    if self.l0_sub >= L0_STOP {
+      Stall::Stop(Reason::L0Files)
+    } else if pending >= PENDING_STOP {
+      Stall::Stop(Reason::PendingBytes)
+    } else if self.l0_sub >= L0_SLOWDOWN {
+      Stall::Delay(Reason::L0Files)
+    } else if pending >= PENDING_SLOWDOWN {
+      Stall::Delay(Reason::PendingBytes)
+    } else {
+      Stall::None
+    }
+  }
+
   /// Recompute scores (cold path)
   /// 重新计算分数（冷路径）
-  fn update(&mut self) {
+  pub(super) fn update(&mut self) {
     if !self.dirty {
       return;
     }
-    let (target, base) = level_target_size(self.total_size);
+    let (target, base) = level_target_size(self.total_size, &self.conf);
     self.level_target_size = target;
     self.base_level = find_base_level(base, &self.level_size);
 
     // SAFETY: LEVEL_LEN_MINUS_1=6, index access is safe
     // 安全：LEVEL_LEN_MINUS_1=6，索引访问安全
     unsafe {
-      *self.score.get_unchecked_mut(0) = l0(self.l0_cnt);
+      *self.score.get_unchecked_mut(0) = l0(self.l0_sub, self.conf.l0_max_file);
     }
 
+    // Bottom level and below never sink
+    // 最底层及以下永不下沉
+    let bottom = self.conf.max_level as usize - 1;
+    self.score[bottom + 1..].fill(0);
+
     for (i, (&actual, &target)) in self
       .level_size
       .iter()
       .zip(&self.level_target_size)
-      .take(LEVEL_LEN_MINUS_1 - 1)
+      .take(bottom)
       .enumerate()
     {
       // SAFETY: i is in 0..5, i+1 is in 1..6
@@ -1,33 +1,94 @@
-use jdb_base::{ckp::sst::Sst, sst::Level};
+use jdb_base::{ckp::sst::Sst, sst::Level, stall::Stall};
 
 use super::Score;
-use crate::Id;
+use crate::{Id, conf::Config, sink::Strategy};
 
 impl Score {
+  #[inline]
   pub fn new(iter: impl IntoIterator<Item = (Id, Sst)>) -> Self {
+    Self::with_conf(Config::default(), iter)
+  }
+
+  /// Create with level shape
+  /// 使用层级形态创建
+  pub fn with_conf(conf: Config, iter: impl IntoIterator<Item = (Id, Sst)>) -> Self {
     let mut score = Self {
       level_target_size: Default::default(),
       score: Default::default(),
       total_size: 0,
       l0_cnt: 0,
+      l0_sub: 0,
+      l0_size: 0,
       level_size: Default::default(),
-      base_level: Level::L6,
+      base_level: conf.max_level,
       level_files: Default::default(),
       id_sst: Default::default(),
+      conf,
       dirty: true,
     };
     score.push_iter(iter);
     score
   }
+}
+
+impl Default for Score {
+  #[inline]
+  fn default() -> Self {
+    Self::new([])
+  }
+}
 
+/// Leveled compaction
+/// 分层压缩
+impl Strategy for Score {
   #[inline]
-  pub fn push(&mut self, id: Id, sst: Sst) {
+  fn push(&mut self, id: Id, sst: Sst) {
     self.add(id, &sst);
     self.id_sst.insert(id, sst);
   }
 
   #[inline]
-  pub fn rm<'a>(&mut self, iter: impl IntoIterator<Item = (Level, &'a [Id])>) {
+  fn get(&self, id: Id) -> Option<&Sst> {
+    self.id_sst.get(&id)
+  }
+
+  #[inline]
+  fn l0_sublevels(&mut self, count: u64) {
+    if self.l0_sub != count {
+      self.l0_sub = count;
+      self.dirty = true;
+    }
+  }
+
+  #[inline]
+  fn pending_bytes(&mut self) -> u64 {
+    self.pending()
+  }
+
+  fn target(&mut self, level: Level) -> Option<(u32, u64)> {
+    self.update();
+    let score = self.score.get(level as usize).copied().unwrap_or(0);
+    let target = match level {
+      Level::L0 => 0,
+      // SAFETY: Level L1-L6 maps to index 0-5
+      // 安全：Level L1-L6 对应索引 0-5
+      _ => unsafe { *self.level_target_size.get_unchecked(level as usize - 1) },
+    };
+    Some((score, target))
+  }
+
+  #[inline]
+  fn max_level(&self) -> Level {
+    self.conf.max_level
+  }
+
+  #[inline]
+  fn stall(&mut self) -> Stall {
+    self.write_stall()
+  }
+
+  #[inline]
+  fn rm<'a>(&mut self, iter: impl IntoIterator<Item = (Level, &'a [Id])>) {
     for (level, ids) in iter {
       for &id in ids {
         if let Some(sst) = self.id_sst.remove(&id) {
@@ -37,14 +98,4 @@ impl Score {
       }
     }
   }
-
-  #[inline]
-  pub fn sink<'a>(
-    &mut self,
-    add: impl IntoIterator<Item = (Id, Sst)>,
-    rm: impl IntoIterator<Item = (Level, &'a [Id])>,
-  ) {
-    self.push_iter(add);
-    self.rm(rm);
-  }
 }
@@ -0,0 +1,69 @@
+use jdb_base::{ckp::sst::Sst, sst::Level, stall::Stall};
+
+use super::Sink;
+use crate::Id;
+
+/// Compaction strategy, yields the next Sink to run
+/// 压缩策略，产出下一个待执行的 Sink
+pub trait Strategy: Iterator<Item = Sink> {
+  /// Add SST to state
+  /// 将 SST 添加到状态中
+  fn push(&mut self, id: Id, sst: Sst);
+
+  /// Remove SSTs from state
+  /// 从状态中移除 SST
+  fn rm<'a>(&mut self, iter: impl IntoIterator<Item = (Level, &'a [Id])>);
+
+  /// Get Sst by id
+  /// 按 ID 获取 Sst
+  fn get(&self, id: Id) -> Option<&Sst>;
+
+  /// L0 sublevel count after each applied op, the read amplification of L0
+  /// 每次应用操作后的 L0 子层数，即 L0 的读放大
+  #[inline]
+  fn l0_sublevels(&mut self, _count: u64) {}
+
+  /// Estimated bytes still to be compacted, used to tune I/O rate
+  /// 预估仍需压缩的字节数，用于调整 I/O 速率
+  #[inline]
+  fn pending_bytes(&mut self) -> u64 {
+    0
+  }
+
+  /// (score, target size) of a level, None if the strategy has no targets
+  /// 某层的（得分，目标大小），策略无目标时为 None
+  #[inline]
+  fn target(&mut self, _level: Level) -> Option<(u32, u64)> {
+    None
+  }
+
+  /// Deepest level files may live in
+  /// 文件可存放的最深层级
+  #[inline]
+  fn max_level(&self) -> Level {
+    Level::L6
+  }
+
+  /// Write stall wanted by current state
+  /// 当前状态所需的写入停顿
+  #[inline]
+  fn stall(&mut self) -> Stall {
+    Stall::None
+  }
+
+  /// Apply compaction result: remove old SSTs, then add new ones
+  /// (an id in both is a trivial move to another level)
+  /// 应用压缩结果：先移除旧 SST，再添加新 SST
+  /// （同时出现在两者中的 id 是移动到另一层的平凡移动）
+  #[inline]
+  fn sink<'a>(
+    &mut self,
+    add: impl IntoIterator<Item = (Id, Sst)>,
+    rm: impl IntoIterator<Item = (Level, &'a [Id])>,
+  ) {
+    self.rm(rm);
+    for (id, sst) in add {
+      self.push(id, sst);
+    }
+  }
+}
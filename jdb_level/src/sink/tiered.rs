@@ -0,0 +1,230 @@
+//! Size-tiered (universal) compaction
+//! 分级（通用）压缩
+//!
+//! Each L0 file and each non-empty level L1-L6 is one sorted run.
+//! Runs are merged by size ratio, from newest to oldest.
+//! 每个 L0 文件和每个非空层 L1-L6 各是一个有序段。
+//! 按大小比例从新到旧合并有序段。
+
+use std::collections::HashMap;
+
+use jdb_base::{ckp::sst::Sst, sst::Level};
+
+use super::{LevelSize, Sink, Strategy};
+use crate::{Id, conf::default::L0_MAX_FILE};
+
+/// Sorted run: one L0 file or one whole level
+/// 有序段：一个 L0 文件或一整层
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Run {
+  L0(Id),
+  Level(Level),
+}
+
+/// Size-tiered compaction state
+/// 分级压缩状态
+#[derive(Debug)]
+pub struct Tiered {
+  /// Merge next run if its size <= accumulated * (100 + size_ratio) / 100
+  /// 若下一段大小 <= 累计大小 * (100 + size_ratio) / 100 则合并
+  pub size_ratio: u64,
+  /// Min runs per merge
+  /// 每次合并的最少段数
+  pub min_merge_width: usize,
+  /// Max runs per merge
+  /// 每次合并的最多段数
+  pub max_merge_width: usize,
+  /// Max size amplification (percent) before full merge
+  /// 触发全量合并的最大空间放大（百分比）
+  pub max_size_amp: u64,
+  /// Sorted run count to trigger compaction
+  /// 触发压缩的有序段数量
+  pub trigger: usize,
+  /// L0 files (id, virtual size) sorted by id, oldest first
+  /// L0 文件（id，虚拟大小），按 id 排序，最旧在前
+  pub l0: Vec<(Id, u64)>,
+  pub level_size: LevelSize,
+  /// Id to Sst mapping
+  /// ID 到 Sst 的映射
+  pub id_sst: HashMap<Id, Sst>,
+}
+
+impl Default for Tiered {
+  fn default() -> Self {
+    Self {
+      size_ratio: 1,
+      min_merge_width: 2,
+      max_merge_width: usize::MAX,
+      max_size_amp: 200,
+      trigger: L0_MAX_FILE as usize,
+      l0: Vec::new(),
+      level_size: Default::default(),
+      id_sst: HashMap::new(),
+    }
+  }
+}
+
+impl Tiered {
+  /// Sorted runs with virtual size, newest first
+  /// 有序段及其虚拟大小，最新在前
+  pub fn runs(&self) -> Vec<(Run, u64)> {
+    let mut runs: Vec<_> = self
+      .l0
+      .iter()
+      .rev()
+      .map(|&(id, size)| (Run::L0(id), size))
+      .collect();
+    for (i, &size) in self.level_size.iter().enumerate() {
+      if size > 0 {
+        // SAFETY: i is in 0..6, i+1 is a valid Level L1-L6
+        // 安全：i 在 0..6，i+1 是有效的 Level L1-L6
+        let level: Level = unsafe { std::mem::transmute((i + 1) as u8) };
+        runs.push((Run::Level(level), size));
+      }
+    }
+    runs
+  }
+
+  /// Build Sink for picked runs, `next` is the run right after them (older)
+  /// 为选中的段构建 Sink，`next` 是紧随其后（更旧）的段
+  fn tier(picked: &[(Run, u64)], newest: bool, next: Option<&(Run, u64)>) -> Option<Sink> {
+    let mut l0 = Vec::new();
+    let mut levels = Vec::new();
+    for &(run, _) in picked {
+      match run {
+        Run::L0(id) => l0.push(id),
+        Run::Level(level) => levels.push(level),
+      }
+    }
+
+    // Output goes to the deepest picked level,
+    // or right above the next run when only L0 files are picked
+    // 输出到选中的最深层，若只选中 L0 文件则输出到下一段的上一层
+    let to = match (levels.last(), next) {
+      (Some(&level), _) => level,
+      (None, None) => Level::L6,
+      (None, Some(&(Run::Level(level), _))) => {
+        let to = level.prev()?;
+        // Output into L0 would sit above any newer L0 files left behind
+        // 输出到 L0 会位于遗留的更新 L0 文件之上
+        if to == Level::L0 && !newest {
+          return None;
+        }
+        to
+      }
+      // Older L0 files remain, output must stay the newest
+      // 仍有更旧的 L0 文件，输出必须保持最新
+      (None, Some(&(Run::L0(_), _))) => {
+        if !newest {
+          return None;
+        }
+        Level::L0
+      }
+    };
+
+    Some(Sink::Tier { l0, levels, to })
+  }
+}
+
+impl Iterator for Tiered {
+  type Item = Sink;
+
+  /// Get next merge of sorted runs
+  /// 获取下一次有序段合并
+  fn next(&mut self) -> Option<Self::Item> {
+    let runs = self.runs();
+    let min_width = self.min_merge_width.max(2);
+    if runs.len() < self.trigger.max(min_width) {
+      return None;
+    }
+
+    // 1. Size amplification: merge everything into the oldest run
+    // 1. 空间放大：将全部合并到最旧的段
+    if let Some((&(_, last), rest)) = runs.split_last()
+      && last > 0
+    {
+      let rest: u64 = rest.iter().map(|&(_, s)| s).sum();
+      if rest.saturating_mul(100) >= last.saturating_mul(self.max_size_amp) {
+        return Self::tier(&runs, true, None);
+      }
+    }
+
+    // 2. Size ratio: merge consecutive runs of similar size
+    // 2. 大小比例：合并大小相近的连续段
+    let max_width = self.max_merge_width.max(min_width);
+    for start in 0..runs.len() {
+      let mut acc = runs[start].1;
+      let mut end = start + 1;
+      while end < runs.len() && end - start < max_width {
+        let size = runs[end].1;
+        if size.saturating_mul(100) > acc.saturating_mul(100 + self.size_ratio) {
+          break;
+        }
+        acc = acc.saturating_add(size);
+        end += 1;
+      }
+      if end - start >= min_width
+        && let Some(sink) = Self::tier(&runs[start..end], start == 0, runs.get(end))
+      {
+        return Some(sink);
+      }
+    }
+
+    // 3. Too many runs: merge newest ones until below trigger
+    // 3. 段过多：合并最新的段直到低于触发值
+    let n = (runs.len() + 2)
+      .saturating_sub(self.trigger)
+      .clamp(min_width, runs.len());
+    Self::tier(&runs[..n], true, runs.get(n))
+  }
+}
+
+impl Strategy for Tiered {
+  fn push(&mut self, id: Id, sst: Sst) {
+    let size = sst.virtual_size();
+    if sst.level == Level::L0 {
+      let pos = self.l0.partition_point(|&(i, _)| i < id);
+      self.l0.insert(pos, (id, size));
+    } else {
+      // SAFETY: Level L1-L6 maps to index 0-5
+      // 安全：Level L1-L6 对应索引 0-5
+      let lsize = unsafe { self.level_size.get_unchecked_mut(sst.level as usize - 1) };
+      *lsize = lsize.saturating_add(size);
+    }
+    self.id_sst.insert(id, sst);
+  }
+
+  #[inline]
+  fn get(&self, id: Id) -> Option<&Sst> {
+    self.id_sst.get(&id)
+  }
+
+  /// Every run except the oldest, once run count reaches trigger
+  /// 有序段数量达到触发值后，除最旧段外的全部段
+  fn pending_bytes(&mut self) -> u64 {
+    let runs = self.runs();
+    if runs.len() < self.trigger.max(2) {
+      return 0;
+    }
+    runs[..runs.len() - 1].iter().map(|&(_, s)| s).sum()
+  }
+
+  fn rm<'a>(&mut self, iter: impl IntoIterator<Item = (Level, &'a [Id])>) {
+    for (level, ids) in iter {
+      for &id in ids {
+        let Some(sst) = self.id_sst.remove(&id) else {
+          continue;
+        };
+        debug_assert_eq!(sst.level, level);
+        if level == Level::L0 {
+          self.l0.retain(|&(i, _)| i != id);
+        } else {
+          // SAFETY: Level L1-L6 maps to index 0-5
+          // 安全：Level L1-L6 对应索引 0-5
+          let lsize = unsafe { self.level_size.get_unchecked_mut(level as usize - 1) };
+          *lsize = lsize.saturating_sub(sst.virtual_size());
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,131 @@
+//! Level statistics and write amplification
+//! 层级统计与写放大
+
+use std::fmt;
+
+use jdb_base::sst::Level;
+
+use crate::{Levels, sink::Strategy};
+
+/// Cumulative I/O bytes since open
+/// 自打开以来累计的 I/O 字节数
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Io {
+  /// Bytes flushed from memtable
+  /// 从内存表刷盘的字节数
+  pub flush: u64,
+  /// Bytes read by compaction
+  /// 压缩读取的字节数
+  pub read: u64,
+  /// Bytes written by compaction
+  /// 压缩写入的字节数
+  pub write: u64,
+}
+
+impl Io {
+  /// Write amplification: (flush + compaction write) / flush
+  /// 写放大：(刷盘 + 压缩写入) / 刷盘
+  #[inline]
+  pub fn write_amp(&self) -> f64 {
+    if self.flush == 0 {
+      return 0.0;
+    }
+    self.flush.saturating_add(self.write) as f64 / self.flush as f64
+  }
+}
+
+/// Statistics of one level
+/// 单层统计
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelStats {
+  pub level: Level,
+  /// File count
+  /// 文件数
+  pub files: usize,
+  /// Bytes on disk
+  /// 磁盘字节数
+  pub size: u64,
+  /// Size including removed entries (`Sst::virtual_size`)
+  /// 含已删除条目的大小（`Sst::virtual_size`）
+  pub virtual_size: u64,
+  /// Sink score, `SCALE` means 100%
+  /// 下沉得分，`SCALE` 代表 100%
+  pub score: u32,
+  /// Target size, 0 if none
+  /// 目标大小，无则为 0
+  pub target: u64,
+}
+
+/// Levels report, printable as a table
+/// 层级报告，可打印为表格
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+  pub levels: [LevelStats; Level::LEN],
+  pub io: Io,
+}
+
+impl<S: Strategy> Levels<S> {
+  /// Collect per-level statistics and cumulative I/O
+  /// 收集每层统计和累计 I/O
+  pub fn stats(&mut self) -> Stats {
+    let levels = std::array::from_fn(|i| {
+      // SAFETY: i is in 0..7, a valid Level
+      // 安全：i 在 0..7，是有效的 Level
+      let level: Level = unsafe { std::mem::transmute(i as u8) };
+      let mut stats = LevelStats {
+        level,
+        files: 0,
+        size: 0,
+        virtual_size: 0,
+        score: 0,
+        target: 0,
+      };
+      for m in self.files(level) {
+        stats.files += 1;
+        if let Some(sst) = self.sink.get(m.id) {
+          stats.size += sst.size;
+          stats.virtual_size += sst.virtual_size();
+        }
+      }
+      if let Some((score, target)) = self.sink.target(level) {
+        stats.score = score;
+        stats.target = target;
+      }
+      stats
+    });
+    Stats {
+      levels,
+      io: self.io,
+    }
+  }
+}
+
+impl fmt::Display for Stats {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(
+      f,
+      "{:<5} {:>6} {:>14} {:>14} {:>7} {:>14}",
+      "Level", "Files", "Size", "Virtual", "Score", "Target"
+    )?;
+    for s in &self.levels {
+      writeln!(
+        f,
+        "{:<5} {:>6} {:>14} {:>14} {:>7.2} {:>14}",
+        format!("{:?}", s.level),
+        s.files,
+        s.size,
+        s.virtual_size,
+        s.score as f64 / crate::sink::SCALE as f64,
+        s.target
+      )?;
+    }
+    write!(
+      f,
+      "flush {} read {} write {} write-amp {:.2}",
+      self.io.flush,
+      self.io.read,
+      self.io.write,
+      self.io.write_amp()
+    )
+  }
+}
@@ -0,0 +1,171 @@
+//! Compaction task planned from a Sink
+//! 由 Sink 规划出的压缩任务
+
+use jdb_base::sst::{self, Level, Op};
+
+use crate::{Id, Meta};
+
+/// Input files grouped by level
+/// 按层级分组的输入文件
+pub type Input = Vec<(Level, Vec<Meta>)>;
+
+/// Compaction task
+/// 压缩任务
+#[derive(Debug)]
+pub enum Task {
+  /// Rewrite input files into a level
+  /// 将输入文件重写到某层
+  Merge(Merge),
+  /// Remove files without rewrite
+  /// 不重写直接移除文件
+  Rm(Rm),
+  /// Metadata-only move to another level, apply the Op directly
+  /// 仅元数据移动到另一层，直接应用 Op
+  Move(Op),
+}
+
+/// Merge input files into `to`
+/// 将输入文件合并到 `to`
+#[derive(Debug)]
+pub struct Merge {
+  pub to: Level,
+  /// Holding Meta keeps input files alive until the task is dropped
+  /// 持有 Meta 使输入文件在任务释放前保持存活
+  pub input: Input,
+  /// No older data below the output, bottommost tombstones can be dropped
+  /// (see `jdb_base::Purge`)
+  /// 输出下方无更老数据，可丢弃最底层墓碑（见 `jdb_base::Purge`）
+  pub bottom: bool,
+}
+
+/// Key range piece of a merge: [start, end), None is unbounded.
+/// Each piece is merged independently and must only emit keys in its range.
+/// Holds plain metadata so it can be sent to another thread,
+/// the `Merge` it came from keeps the input files alive
+/// 合并的键范围分片：[start, end)，None 表示无界。
+/// 每个分片独立合并，且只能输出其范围内的键。
+/// 只持有普通元数据以便发送到其他线程，其来源 `Merge` 保持输入文件存活
+#[derive(Debug)]
+pub struct Sub {
+  pub start: Option<Box<[u8]>>,
+  pub end: Option<Box<[u8]>>,
+  /// Input files overlapping the piece
+  /// 与分片重叠的输入文件
+  pub input: Vec<(Level, Vec<sst::Meta>)>,
+}
+
+impl Sub {
+  /// Check if key belongs to this piece
+  /// 检查键是否属于该分片
+  #[inline]
+  pub fn contains(&self, key: &[u8]) -> bool {
+    self.start.as_deref().is_none_or(|s| key >= s) && self.end.as_deref().is_none_or(|e| key < e)
+  }
+}
+
+/// Remove files, their entries should be handed to Discard (see `crate::discard`)
+/// 移除文件，其条目应交给 Discard（见 `crate::discard`）
+#[derive(Debug)]
+pub struct Rm {
+  pub input: Input,
+}
+
+/// Ids of input files grouped by level
+/// 按层级分组的输入文件 ID
+#[inline]
+fn ids(input: &Input) -> Vec<(Level, Vec<Id>)> {
+  input
+    .iter()
+    .filter(|(_, meta_li)| !meta_li.is_empty())
+    .map(|(level, meta_li)| (*level, meta_li.iter().map(|m| m.id).collect()))
+    .collect()
+}
+
+impl Merge {
+  /// Build Op to commit merge output
+  /// 构建提交合并输出的 Op
+  #[inline]
+  pub fn op(&self, add: Vec<jdb_base::ckp::sst::Meta>) -> Op {
+    Op::Compact {
+      add,
+      rm: ids(&self.input),
+    }
+  }
+
+  /// Build one Op committing the outputs of all sub-compactions
+  /// 构建一次性提交所有子压缩输出的 Op
+  #[inline]
+  pub fn join(&self, outs: impl IntoIterator<Item = Vec<jdb_base::ckp::sst::Meta>>) -> Op {
+    self.op(outs.into_iter().flatten().collect())
+  }
+
+  /// Split into at most `max` pieces at file boundaries of the output level,
+  /// so pieces can be merged in parallel (across threads or interleaved)
+  /// 按输出层的文件边界拆分为至多 `max` 个分片，以便并行合并（跨线程或交错执行）
+  pub fn split(&self, max: usize) -> Vec<Sub> {
+    let mut bounds: Vec<&[u8]> = if self.to == Level::L0 {
+      Vec::new()
+    } else {
+      self
+        .input
+        .iter()
+        .filter(|(level, _)| *level == self.to)
+        .flat_map(|(_, meta_li)| meta_li.iter().map(|m| m.min.as_ref()))
+        .collect()
+    };
+    bounds.sort_unstable();
+    bounds.dedup();
+    // First file start is not a boundary
+    // 第一个文件的起点不是边界
+    if !bounds.is_empty() {
+      bounds.remove(0);
+    }
+
+    let max = max.max(1);
+    let step = (bounds.len() + 1).div_ceil(max);
+    let mut edges: Vec<Option<Box<[u8]>>> = vec![None];
+    edges.extend(
+      bounds
+        .into_iter()
+        .skip(step - 1)
+        .step_by(step)
+        .map(|b| Some(b.into())),
+    );
+    edges.push(None);
+
+    edges
+      .windows(2)
+      .map(|w| {
+        let (start, end) = (w[0].clone(), w[1].clone());
+        let input = self
+          .input
+          .iter()
+          .map(|(level, meta_li)| {
+            let li = meta_li
+              .iter()
+              .filter(|m| {
+                start.as_deref().is_none_or(|s| m.max.as_ref() >= s)
+                  && end.as_deref().is_none_or(|e| m.min.as_ref() < e)
+              })
+              .map(|m| (**m).clone())
+              .collect();
+            (*level, li)
+          })
+          .collect();
+        Sub { start, end, input }
+      })
+      .collect()
+  }
+}
+
+impl Rm {
+  /// Build Op to commit removal
+  /// 构建提交移除的 Op
+  #[inline]
+  pub fn op(&self) -> Op {
+    Op::Compact {
+      add: Vec::new(),
+      rm: ids(&self.input),
+    }
+  }
+}
@@ -0,0 +1,138 @@
+use jdb_base::{
+  ckp::sst::{
+    Meta,
+    ckp::{Levels as _, Op},
+  },
+  sst::Level,
+};
+use jdb_level::{Levels, error::Error, sink::Strategy as _};
+
+mod common;
+
+use common::lru;
+
+fn meta(id: u64, level: Level, min: &[u8], max: &[u8]) -> Meta {
+  common::meta(id, level, 10, min, max)
+}
+
+#[test]
+fn test_recover_check() {
+  let err = Levels::new(
+    lru(),
+    [
+      meta(1, Level::L1, b"a", b"m"),
+      meta(2, Level::L1, b"k", b"z"),
+    ],
+  )
+  .unwrap_err();
+  assert_eq!(
+    err,
+    Error::Overlap {
+      level: Level::L1,
+      id: 2,
+      other: 1
+    }
+  );
+
+  let err = Levels::new(lru(), [meta(1, Level::L0, b"z", b"a")]).unwrap_err();
+  assert_eq!(err, Error::Range(1));
+
+  let err = Levels::new(
+    lru(),
+    [
+      meta(1, Level::L0, b"a", b"b"),
+      meta(1, Level::L2, b"a", b"b"),
+    ],
+  )
+  .unwrap_err();
+  assert_eq!(err, Error::Exists(1));
+}
+
+#[compio::test]
+async fn test_verify_error() {
+  let mut levels = Levels::new(
+    lru(),
+    [
+      meta(1, Level::L1, b"a", b"c"),
+      meta(2, Level::L1, b"x", b"z"),
+    ],
+  )
+  .unwrap();
+
+  let err = levels
+    .verify(&Op::Mem2SstWal {
+      meta: meta(3, Level::L1, b"b", b"d"),
+      wal_id: 3,
+    })
+    .unwrap_err();
+  assert_eq!(
+    err,
+    Error::Overlap {
+      level: Level::L1,
+      id: 3,
+      other: 1
+    }
+  );
+
+  let err = levels
+    .verify(&Op::Compact {
+      add: vec![meta(4, Level::L2, b"a", b"b")],
+      rm: vec![(Level::L1, vec![1, 9])],
+    })
+    .unwrap_err();
+  assert_eq!(
+    err,
+    Error::NotFound {
+      level: Level::L1,
+      id: 9
+    }
+  );
+  // Verify leaves state untouched
+  // 校验不改变状态
+  assert_eq!(levels.files(Level::L1).count(), 2);
+  assert!(levels.files(Level::L2).next().is_none());
+
+  // Replacing the overlapped file is fine
+  // 替换被重叠的文件是允许的
+  levels
+    .update(Op::Compact {
+      add: vec![meta(3, Level::L1, b"b", b"d")],
+      rm: vec![(Level::L1, vec![1])],
+    })
+    .unwrap();
+  levels.check().unwrap();
+
+  assert_eq!(
+    levels.sorted(Level::L0).unwrap_err(),
+    Error::Level(Level::L0)
+  );
+  assert_eq!(
+    levels
+      .overlap(Level::L0, &(b"a".as_slice()..=b"z".as_slice()))
+      .unwrap_err(),
+    Error::Level(Level::L0)
+  );
+
+  // A failed update leaves levels and strategy as they were
+  // 失败的更新使层级和策略保持原样
+  let io = levels.io;
+  levels
+    .update(Op::Mem2SstWal {
+      meta: meta(5, Level::L1, b"c", b"y"),
+      wal_id: 5,
+    })
+    .unwrap_err();
+  levels
+    .update(Op::Compact {
+      add: vec![meta(6, Level::L2, b"a", b"z")],
+      rm: vec![(Level::L1, vec![2]), (Level::L1, vec![9])],
+    })
+    .unwrap_err();
+  assert!(levels.sink.get(5).is_none());
+  assert!(levels.sink.get(6).is_none());
+  assert!(levels.sink.get(2).is_some());
+  assert_eq!(levels.files(Level::L1).count(), 2);
+  assert_eq!(levels.wal_id, 0);
+  assert_eq!(levels.io, io);
+  levels.check().unwrap();
+}
@@ -0,0 +1,41 @@
+//! Fixtures shared by the integration tests
+//! 集成测试共享的测试夹具
+
+#![allow(dead_code)]
+
+use std::{cell::RefCell, rc::Rc};
+
+use file_lru::FileLru;
+use jdb_base::{
+  ckp::sst::{Meta, Sst},
+  sst::{self, Level},
+};
+use jdb_level::Lru;
+
+#[static_init::constructor(0)]
+extern "C" fn _log_init() {
+  log_init::init();
+}
+
+pub fn lru() -> Lru {
+  Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)))
+}
+
+pub fn sst(level: Level, size: u64) -> Sst {
+  Sst {
+    level,
+    rmed: 0,
+    size,
+  }
+}
+
+pub fn meta(id: u64, level: Level, size: u64, min: &[u8], max: &[u8]) -> Meta {
+  Meta {
+    sst: sst(level, size),
+    meta: sst::Meta {
+      id,
+      min: min.into(),
+      max: max.into(),
+    },
+  }
+}
@@ -0,0 +1,74 @@
+use std::{cell::RefCell, rc::Rc};
+
+use file_lru::FileLru;
+use jdb_base::{
+  ckp::sst::Meta,
+  sst::{self, Level},
+};
+use jdb_level::{
+  Levels, Lru,
+  conf::{Conf, Config},
+  error::Error,
+  sink::{Score, Sink, Strategy},
+};
+
+mod common;
+
+use common::sst;
+
+fn conf() -> Config {
+  Config::from(
+    &[
+      Conf::MaxLevel(Level::L3),
+      Conf::BaseSize(1000),
+      Conf::Fanout(10),
+      Conf::L0MaxFile(2),
+    ][..],
+  )
+}
+
+#[test]
+fn test_conf() {
+  let config = Config::from(&[Conf::MaxLevel(Level::L0), Conf::Fanout(0)][..]);
+  assert_eq!(config.max_level, Level::L6);
+  assert_eq!(config.fanout, 2);
+}
+
+#[test]
+fn test_score_shape() {
+  let mut score = Score::with_conf(conf(), []);
+  for id in 1..=2 {
+    score.push(id, sst(Level::L0, 10));
+  }
+  // Score alone knows no key ranges, Levels reports sublevels
+  // Score 自身不知道键范围，子层数由 Levels 上报
+  score.l0_sublevels(2);
+  assert_eq!(score.next(), Some(Sink::L0(Level::L3)));
+
+  // Bottom is L3, so the base level rises to L2
+  // 最底层为 L3，基础层上移到 L2
+  score.push(3, sst(Level::L3, 10_000));
+  assert_eq!(score.next(), Some(Sink::L0(Level::L2)));
+  assert_eq!(score.max_level(), Level::L3);
+}
+
+#[test]
+fn test_recover_shape() {
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)));
+  let meta = Meta {
+    sst: sst(Level::L4, 10),
+    meta: sst::Meta {
+      id: 1,
+      min: b"a".as_slice().into(),
+      max: b"b".as_slice().into(),
+    },
+  };
+  let err = Levels::with_sink(lru, Score::with_conf(conf(), []), [meta]).unwrap_err();
+  assert_eq!(
+    err,
+    Error::Shape {
+      level: Level::L4,
+      id: 1
+    }
+  );
+}
@@ -0,0 +1,37 @@
+use std::{cell::RefCell, fs, rc::Rc};
+
+use file_lru::FileLru;
+use ider::path::id_path;
+use jdb_base::{ckp::sst::Meta, sst::Level};
+use jdb_level::{Levels, Lru};
+
+mod common;
+
+fn meta(id: u64) -> Meta {
+  common::meta(id, Level::L1, 10, b"a", b"b")
+}
+
+#[test]
+fn test_gc() {
+  let dir = tempfile::tempdir().unwrap();
+  let live = id_path(dir.path(), 1);
+  let orphan = id_path(dir.path(), 2);
+  let tmp = dir.path().join(format!("{}.tmp", ider::path::encode(3)));
+  let other = dir.path().join("notes.txt");
+  for p in [&live, &orphan, &tmp, &other] {
+    fs::write(p, b"x").unwrap();
+  }
+
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new(dir.path(), 16)));
+  let levels = Levels::new(lru, [meta(1)]).unwrap();
+
+  let gc = levels.gc(true).unwrap();
+  assert_eq!(gc.sst, vec![orphan.clone()]);
+  assert_eq!(gc.tmp, vec![tmp.clone()]);
+  assert!(orphan.exists() && tmp.exists());
+
+  let gc = levels.gc(false).unwrap();
+  assert!(gc.fail.is_empty());
+  assert!(!orphan.exists() && !tmp.exists());
+  assert!(live.exists() && other.exists());
+}
@@ -0,0 +1,143 @@
+use jdb_base::{
+  ckp::sst::{Meta, ckp::Levels as _},
+  sst::{Level, Op},
+};
+use jdb_level::{Levels, Task, sink::Sink};
+
+mod common;
+
+use common::lru;
+
+fn meta(id: u64, min: &[u8], max: &[u8]) -> Meta {
+  common::meta(id, Level::L0, 10, min, max)
+}
+
+fn sublevels(levels: &Levels) -> Vec<Vec<u64>> {
+  levels
+    .l0
+    .iter()
+    .map(|sub| sub.iter().map(|m| m.id).collect())
+    .collect()
+}
+
+#[compio::test]
+async fn test_sublevel() {
+  let mut levels = Levels::new(
+    lru(),
+    [
+      meta(2, b"c", b"d"),
+      meta(1, b"a", b"b"),
+      meta(3, b"a", b"c"),
+    ],
+  )
+  .unwrap();
+  // 1 and 2 are disjoint, 3 overlaps both
+  // 1 与 2 不重叠，3 与两者都重叠
+  assert_eq!(sublevels(&levels), vec![vec![1, 2], vec![3]]);
+
+  levels
+    .update(Op::Mem2SstWal {
+      meta: meta(4, b"x", b"z"),
+      wal_id: 4,
+    })
+    .unwrap();
+  assert_eq!(sublevels(&levels), vec![vec![1, 2, 4], vec![3]]);
+
+  // Newest sublevel first
+  // 最新子层在前
+  let range = b"b".as_slice()..=b"b".as_slice();
+  let ids: Vec<u64> = levels.overlap_l0(&range).map(|m| m.id).collect();
+  assert_eq!(ids, vec![3, 1]);
+
+  levels
+    .update(Op::Compact {
+      add: vec![],
+      rm: vec![(Level::L0, vec![3])],
+    })
+    .unwrap();
+  assert_eq!(sublevels(&levels), vec![vec![1, 2, 4]]);
+  levels.check().unwrap();
+}
+
+#[test]
+fn test_intra_l0() {
+  let levels = Levels::new(lru(), (1..=5).map(|id| meta(id, b"a", b"z"))).unwrap();
+  assert_eq!(levels.l0.len(), 5);
+
+  let Some(Task::Merge(merge)) = levels.plan(Sink::IntraL0(Level::L6)) else {
+    panic!("expect Task::Merge");
+  };
+  assert_eq!(merge.to, Level::L0);
+  let ids: Vec<u64> = merge.input[0].1.iter().map(|m| m.id).collect();
+  assert_eq!(ids, vec![5, 4, 3, 2, 1]);
+
+  // Too few files: fall back to sink into base level
+  // 文件太少：回退为下沉到基础层
+  let levels = Levels::new(lru(), [meta(1, b"a", b"b"), meta(2, b"a", b"b")]).unwrap();
+  let Some(Task::Merge(merge)) = levels.plan(Sink::IntraL0(Level::L6)) else {
+    panic!("expect Task::Merge");
+  };
+  assert_eq!(merge.to, Level::L6);
+}
+
+#[compio::test]
+async fn test_intra_l0_keeps_newer_flush() {
+  let mut levels = Levels::new(lru(), (1..=4).map(|id| meta(id, b"a", b"z"))).unwrap();
+  let Some(Task::Merge(merge)) = levels.plan(Sink::IntraL0(Level::L6)) else {
+    panic!("expect Task::Merge");
+  };
+  assert_eq!(merge.input[0].1.len(), 4);
+
+  // A flush lands while the merge runs, then the merge output gets a larger id
+  // 合并运行期间完成一次刷盘，随后合并输出获得更大的 id
+  levels
+    .update(Op::Mem2SstWal {
+      meta: meta(6, b"a", b"z"),
+      wal_id: 6,
+    })
+    .unwrap();
+  levels
+    .update(Op::Compact {
+      add: vec![meta(7, b"a", b"z")],
+      rm: vec![(Level::L0, vec![1, 2, 3, 4])],
+    })
+    .unwrap();
+  drop(merge);
+  // Output stays below the newer flush
+  // 输出保持在更新的刷盘之下
+  assert_eq!(sublevels(&levels), vec![vec![7], vec![6]]);
+
+  levels
+    .update(Op::Mem2SstWal {
+      meta: meta(8, b"a", b"z"),
+      wal_id: 8,
+    })
+    .unwrap();
+  assert_eq!(sublevels(&levels), vec![vec![7], vec![6], vec![8]]);
+  levels.check().unwrap();
+
+  // Two newest by id are 8 and 7, but by position they are 8 and 6
+  // 按 id 最新的两个是 8 和 7，按位置则是 8 和 6
+  let Some(Task::Merge(merge)) = levels.plan(Sink::Tier {
+    l0: vec![8, 7],
+    levels: vec![],
+    to: Level::L0,
+  }) else {
+    panic!("expect Task::Merge");
+  };
+  let ids: Vec<u64> = merge.input[0].1.iter().map(|m| m.id).collect();
+  assert_eq!(ids, vec![8, 6]);
+}
+
+#[test]
+fn test_score_sublevels() {
+  // Disjoint files are one sublevel, nothing to sink
+  // 互不重叠的文件是一个子层，无需下沉
+  let mut levels = Levels::new(
+    lru(),
+    (1..=4u8).map(|i| meta(i as u64, &[b'a' + i * 2], &[b'a' + i * 2 + 1])),
+  )
+  .unwrap();
+  assert_eq!(levels.l0.len(), 1);
+  assert_eq!(levels.sink.next(), None);
+}
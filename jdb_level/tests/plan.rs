@@ -0,0 +1,270 @@
+use std::{cell::RefCell, fs, ops::Bound, rc::Rc, time::Duration};
+
+use file_lru::FileLru;
+use jdb_base::{
+  ckp::sst::{Meta, ckp::Levels as _},
+  sst::{Level, Op},
+};
+use jdb_level::{
+  Levels, Lru, Task,
+  sink::{Sink, Strategy},
+};
+
+mod common;
+
+fn meta(id: u64, level: Level, min: &[u8], max: &[u8]) -> Meta {
+  common::meta(id, level, 10, min, max)
+}
+
+#[compio::test]
+async fn test_trivial_move() {
+  let dir = tempfile::tempdir().unwrap();
+  let path = ider::path::id_path(dir.path(), 1);
+  fs::write(&path, b"sst").unwrap();
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new(dir.path(), 16)));
+
+  let mut levels = Levels::new(
+    lru,
+    [
+      meta(1, Level::L1, b"a", b"c"),
+      meta(2, Level::L2, b"x", b"z"),
+    ],
+  )
+  .unwrap();
+
+  let sink = Sink::L1Plus {
+    from: Level::L1,
+    to: Level::L2,
+    id: 1,
+  };
+  let Some(Task::Move(op)) = levels.plan(sink) else {
+    panic!("expect Task::Move");
+  };
+  let Op::Compact { add, rm } = &op else {
+    panic!("expect Op::Compact");
+  };
+  assert_eq!(add.len(), 1);
+  assert_eq!(add[0].sst.level, Level::L2);
+  assert_eq!(rm, &vec![(Level::L1, vec![1])]);
+
+  levels.update(op).unwrap();
+  assert!(levels.files(Level::L1).next().is_none());
+  let l2: Vec<u64> = levels.files(Level::L2).map(|m| m.id).collect();
+  assert_eq!(l2, vec![1, 2]);
+  assert_eq!(levels.sink.get(1).unwrap().level, Level::L2);
+
+  // Moved file must not be deleted
+  // 被移动的文件不能被删除
+  compio::time::sleep(Duration::from_millis(10)).await;
+  assert!(path.exists());
+}
+
+#[test]
+fn test_overlap_merge() {
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)));
+  let levels = Levels::new(
+    lru,
+    [
+      meta(1, Level::L1, b"a", b"m"),
+      meta(2, Level::L2, b"k", b"z"),
+      meta(3, Level::L0, b"a", b"b"),
+      meta(4, Level::L0, b"b", b"c"),
+    ],
+  )
+  .unwrap();
+
+  let Some(Task::Merge(merge)) = levels.plan(Sink::L1Plus {
+    from: Level::L1,
+    to: Level::L2,
+    id: 1,
+  }) else {
+    panic!("expect Task::Merge");
+  };
+  assert_eq!(merge.to, Level::L2);
+  assert_eq!(merge.input[1].1[0].id, 2);
+
+  // Overlapping L0 files can not be moved
+  // 互相重叠的 L0 文件不能移动
+  assert!(matches!(
+    levels.plan(Sink::L0(Level::L3)),
+    Some(Task::Merge(_))
+  ));
+}
+
+#[test]
+fn test_compact_range() {
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)));
+  let levels = Levels::new(
+    lru,
+    [
+      meta(1, Level::L0, b"c", b"d"),
+      meta(2, Level::L1, b"a", b"e"),
+      meta(3, Level::L1, b"x", b"z"),
+      // Reached only through the widened span of id 2
+      // 仅能通过 id 2 扩展后的跨度到达
+      meta(4, Level::L2, b"a", b"b"),
+      meta(5, Level::L3, b"c", b"c"),
+    ],
+  )
+  .unwrap();
+
+  let range: (Bound<&[u8]>, Bound<&[u8]>) = (Bound::Included(b"c"), Bound::Included(b"d"));
+  let Some(Task::Merge(merge)) = levels.compact_range(&range, Level::L2).unwrap() else {
+    panic!("expect Task::Merge");
+  };
+  assert_eq!(merge.to, Level::L2);
+  let ids: Vec<(Level, Vec<u64>)> = merge
+    .input
+    .iter()
+    .map(|(level, li)| (*level, li.iter().map(|m| m.id).collect()))
+    .collect();
+  assert_eq!(
+    ids,
+    vec![
+      (Level::L0, vec![1]),
+      (Level::L1, vec![2]),
+      (Level::L2, vec![4])
+    ]
+  );
+
+  let range: (Bound<&[u8]>, Bound<&[u8]>) = (Bound::Included(b"m"), Bound::Excluded(b"n"));
+  assert!(levels.compact_range(&range, Level::L6).unwrap().is_none());
+  assert!(levels.compact_range(&range, Level::L0).is_err());
+}
+
+#[test]
+fn test_compact_range_l0_fixpoint() {
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)));
+  let levels = Levels::new(
+    lru,
+    [
+      meta(1, Level::L0, b"b", b"c"),
+      // Overlaps id 1 only through the wider L1 file
+      // 仅通过更宽的 L1 文件与 id 1 重叠
+      meta(2, Level::L0, b"f", b"g"),
+      meta(3, Level::L1, b"a", b"h"),
+    ],
+  )
+  .unwrap();
+
+  let range: (Bound<&[u8]>, Bound<&[u8]>) = (Bound::Included(b"b"), Bound::Included(b"b"));
+  let Some(Task::Merge(merge)) = levels.compact_range(&range, Level::L1).unwrap() else {
+    panic!("expect Task::Merge");
+  };
+  let mut l0: Vec<u64> = merge.input[0].1.iter().map(|m| m.id).collect();
+  l0.sort_unstable();
+  assert_eq!(merge.input[0].0, Level::L0);
+  assert_eq!(l0, vec![1, 2]);
+  assert_eq!(merge.input[1].0, Level::L1);
+  assert_eq!(merge.input[1].1[0].id, 3);
+}
+
+#[test]
+fn test_delete_files_in_range() {
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)));
+  let levels = Levels::new(
+    lru,
+    [
+      meta(1, Level::L0, b"c", b"d"),
+      meta(2, Level::L0, b"a", b"z"),
+      meta(3, Level::L1, b"b", b"c"),
+      meta(4, Level::L1, b"e", b"g"),
+      meta(5, Level::L2, b"f", b"h"),
+    ],
+  )
+  .unwrap();
+
+  let range: (Bound<&[u8]>, Bound<&[u8]>) = (Bound::Included(b"b"), Bound::Excluded(b"h"));
+  let Some(Task::Rm(rm)) = levels.delete_files_in_range(&range) else {
+    panic!("expect Task::Rm");
+  };
+  let Op::Compact { add, rm } = rm.op() else {
+    panic!("expect Op::Compact");
+  };
+  assert!(add.is_empty());
+  // 2 and 5 are only partially covered
+  // 2 和 5 仅部分被覆盖
+  assert_eq!(rm, vec![(Level::L0, vec![1]), (Level::L1, vec![3, 4])]);
+
+  let range: (Bound<&[u8]>, Bound<&[u8]>) = (Bound::Included(b"x"), Bound::Unbounded);
+  assert!(levels.delete_files_in_range(&range).is_none());
+}
+
+#[test]
+fn test_split() {
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)));
+  let levels = Levels::new(
+    lru,
+    [
+      meta(1, Level::L0, b"a", b"z"),
+      meta(2, Level::L1, b"a", b"c"),
+      meta(3, Level::L1, b"d", b"f"),
+      meta(4, Level::L1, b"g", b"i"),
+      meta(5, Level::L1, b"j", b"l"),
+    ],
+  )
+  .unwrap();
+  let Some(Task::Merge(merge)) = levels.plan(Sink::L0(Level::L1)) else {
+    panic!("expect Task::Merge");
+  };
+
+  let subs = merge.split(2);
+  assert_eq!(subs.len(), 2);
+  // Pieces can go to worker threads
+  // 分片可以发送给工作线程
+  let subs = std::thread::spawn(move || subs).join().unwrap();
+  assert_eq!(subs[0].end.as_deref(), Some(b"g".as_slice()));
+  assert!(subs[0].contains(b"f") && !subs[0].contains(b"g"));
+  assert!(subs[1].contains(b"g") && subs[1].contains(b"zz"));
+  let ids = |i: usize| -> Vec<u64> {
+    subs[i]
+      .input
+      .iter()
+      .flat_map(|(_, li)| li.iter().map(|m| m.id))
+      .collect()
+  };
+  // L0 file spans both pieces
+  // L0 文件横跨两个分片
+  assert_eq!(ids(0), vec![1, 2, 3]);
+  assert_eq!(ids(1), vec![1, 4, 5]);
+
+  assert_eq!(merge.split(10).len(), 4);
+  assert_eq!(merge.split(1).len(), 1);
+
+  let Op::Compact { add, rm } = merge.join([
+    vec![meta(6, Level::L1, b"a", b"f")],
+    vec![meta(7, Level::L1, b"g", b"z")],
+  ]) else {
+    panic!("expect Op::Compact");
+  };
+  assert_eq!(add.len(), 2);
+  assert_eq!(
+    rm,
+    vec![(Level::L0, vec![1]), (Level::L1, vec![2, 3, 4, 5])]
+  );
+}
+
+#[test]
+fn test_bottom() {
+  let lru: Lru = Rc::new(RefCell::new(FileLru::new("/tmp/jdb_level_test", 16)));
+  let levels = Levels::new(
+    lru,
+    [
+      meta(1, Level::L0, b"a", b"c"),
+      meta(2, Level::L0, b"x", b"z"),
+      meta(3, Level::L2, b"b", b"d"),
+    ],
+  )
+  .unwrap();
+  let merge = |lo: &'static [u8], hi: &'static [u8]| {
+    let range: (Bound<&[u8]>, Bound<&[u8]>) = (Bound::Included(lo), Bound::Included(hi));
+    let Ok(Some(Task::Merge(merge))) = levels.compact_range(&range, Level::L1) else {
+      panic!("expect Task::Merge");
+    };
+    merge
+  };
+  // L2 holds older keys in the span
+  // L2 在该跨度内有更老的键
+  assert!(!merge(b"a", b"c").bottom);
+  assert!(merge(b"x", b"z").bottom);
+}
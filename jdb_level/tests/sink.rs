@@ -0,0 +1,307 @@
+use std::{
+  cell::Cell,
+  future::{Future, ready},
+  pin::Pin,
+  rc::Rc,
+  time::{Duration, Instant},
+};
+
+use jdb_base::sst::Level;
+use jdb_level::{
+  Levels, Task,
+  sink::{Fifo, Score, Sink, Strategy, Tiered},
+};
+
+mod common;
+
+use common::{lru, meta, sst};
+
+#[test]
+fn test_score_l0() {
+  let mut score = Score::default();
+  for id in 1..=4 {
+    score.push(id, sst(Level::L0, 10));
+  }
+  // Disjoint files form one sublevel, no read amplification to fix
+  // 互不重叠的文件构成一个子层，没有需要修复的读放大
+  score.l0_sublevels(1);
+  assert_eq!(score.next(), None);
+  score.l0_sublevels(4);
+  assert_eq!(score.next(), Some(Sink::L0(Level::L6)));
+
+  score.rm([(Level::L0, &[1, 2][..])]);
+  score.l0_sublevels(2);
+  assert_eq!(score.next(), None);
+}
+
+#[test]
+fn test_tiered_size_amp() {
+  let mut tiered = Tiered::default();
+  for id in 1..=3 {
+    tiered.push(id, sst(Level::L0, 10));
+  }
+  // Below trigger
+  // 低于触发值
+  assert_eq!(tiered.next(), None);
+
+  tiered.push(4, sst(Level::L0, 10));
+  assert_eq!(
+    tiered.next(),
+    Some(Sink::Tier {
+      l0: vec![4, 3, 2, 1],
+      levels: vec![],
+      to: Level::L6,
+    })
+  );
+}
+
+#[test]
+fn test_tiered_size_ratio() {
+  let mut tiered = Tiered::default();
+  tiered.push(1, sst(Level::L6, 1000));
+  for id in 2..=5 {
+    tiered.push(id, sst(Level::L0, 10));
+  }
+  // Similar L0 files merge right above L6
+  // 大小相近的 L0 文件合并到 L6 的上一层
+  assert_eq!(
+    tiered.next(),
+    Some(Sink::Tier {
+      l0: vec![5, 4, 3, 2],
+      levels: vec![],
+      to: Level::L5,
+    })
+  );
+
+  tiered.sink([(6, sst(Level::L5, 40))], [(Level::L0, &[2, 3, 4, 5][..])]);
+  assert_eq!(tiered.next(), None);
+}
+
+#[test]
+fn test_tiered_keep_newest_l0() {
+  let mut tiered = Tiered {
+    trigger: 4,
+    ..Tiered::default()
+  };
+  tiered.push(1, sst(Level::L1, 10000));
+  for id in 2..=5 {
+    tiered.push(id, sst(Level::L0, 100));
+  }
+  tiered.push(6, sst(Level::L0, 10));
+  // Older L0 files right above L1 can't go to L0 under the newer id 6
+  // 紧邻 L1 的较旧 L0 文件不能输出到较新的 id 6 之下的 L0
+  assert_eq!(
+    tiered.next(),
+    Some(Sink::Tier {
+      l0: vec![6, 5, 4, 3],
+      levels: vec![],
+      to: Level::L0,
+    })
+  );
+}
+
+#[test]
+fn test_levels_with_sink() {
+  let levels = Levels::with_sink(
+    lru(),
+    Tiered::default(),
+    (1..=4).map(|id| meta(id, Level::L0, 10, b"a", b"z")),
+  )
+  .unwrap();
+  assert_eq!(levels.files(Level::L0).count(), 4);
+  assert_eq!(levels.sink.l0.len(), 4);
+
+  let mut levels =
+    Levels::new(lru(), (1..=4).map(|id| meta(id, Level::L0, 10, b"a", b"z"))).unwrap();
+  assert_eq!(levels.sink.next(), Some(Sink::L0(Level::L6)));
+}
+
+#[test]
+fn test_fifo_size() {
+  let mut fifo = Fifo::new(25, 0);
+  for id in 1..=3 {
+    fifo.push(id, sst(Level::L0, 10));
+  }
+  assert_eq!(fifo.next(), Some(Sink::Drop(vec![(Level::L0, vec![1])])));
+
+  fifo.rm([(Level::L0, &[1][..])]);
+  assert_eq!(fifo.next(), None);
+}
+
+#[test]
+fn test_fifo_ttl() {
+  let mut fifo = Fifo::new(u64::MAX, 3600);
+  // Id 1 was created at the ider epoch, long expired
+  // Id 1 创建于 ider 纪元，早已过期
+  fifo.push(1, sst(Level::L0, 10));
+  let id = ider::Ider::new().get();
+  fifo.push(id, sst(Level::L1, 10));
+  assert_eq!(fifo.next(), Some(Sink::Drop(vec![(Level::L0, vec![1])])));
+}
+
+#[compio::test]
+async fn test_fifo_plan() {
+  use jdb_base::ckp::sst::ckp::{Levels as _, Op};
+
+  let mut levels = Levels::with_sink(
+    lru(),
+    Fifo::new(15, 0),
+    [
+      meta(1, Level::L0, 10, b"a", b"c"),
+      meta(2, Level::L0, 10, b"b", b"d"),
+    ],
+  )
+  .unwrap();
+  let sink = levels.sink.next().unwrap();
+  let Some(Task::Rm(rm)) = levels.plan(sink) else {
+    panic!("expect Task::Rm");
+  };
+  assert_eq!(rm.input.len(), 1);
+  assert_eq!(rm.input[0].1[0].id, 1);
+
+  let op = rm.op();
+  let Op::Compact { add, rm: ids } = &op else {
+    panic!("expect Op::Compact");
+  };
+  assert!(add.is_empty());
+  assert_eq!(ids, &vec![(Level::L0, vec![1])]);
+
+  levels.update(op).unwrap();
+  assert_eq!(levels.files(Level::L0).count(), 1);
+  assert_eq!(levels.sink.next(), None);
+}
+
+#[test]
+fn test_pending_bytes() {
+  let mut score = Score::default();
+  for id in 1..=3 {
+    score.push(id, sst(Level::L0, 10));
+  }
+  score.l0_sublevels(3);
+  assert_eq!(score.pending_bytes(), 0);
+  score.push(4, sst(Level::L0, 10));
+  score.l0_sublevels(4);
+  assert_eq!(score.pending_bytes(), 40);
+
+  let mut tiered = Tiered::default();
+  for id in 1..=4 {
+    tiered.push(id, sst(Level::L0, 10));
+  }
+  tiered.push(5, sst(Level::L6, 100));
+  assert_eq!(tiered.pending_bytes(), 40);
+
+  let mut fifo = Fifo::new(10, 0);
+  fifo.push(1, sst(Level::L0, 100));
+  assert_eq!(fifo.pending_bytes(), 0);
+}
+
+#[compio::test]
+async fn test_tune_rate() {
+  use jdb_base::ckp::sst::ckp::{Levels as _, Op};
+  use jdb_fs::{
+    RateLimit,
+    rate_limit::{Io, Tune},
+  };
+
+  let mut levels =
+    Levels::new(lru(), (1..=4).map(|id| meta(id, Level::L0, 10, b"a", b"z"))).unwrap();
+  let limit = RateLimit::new(0, 0);
+  let tune = Tune {
+    min: 100,
+    max: 300,
+    soft: 0,
+    hard: 80,
+  };
+  levels.set_rate_limit(limit.clone(), tune);
+  assert_eq!(limit.rate(Io::Compact), 200);
+
+  // Compaction caught up: back to the min rate
+  // 压缩已追上：回到最低速率
+  levels
+    .update(Op::Compact {
+      add: vec![meta(5, Level::L6, 40, b"a", b"z")],
+      rm: vec![(Level::L0, vec![1, 2, 3, 4])],
+    })
+    .unwrap();
+  assert_eq!(limit.rate(Io::Compact), 100);
+}
+
+/// Fake clock: sleeping just moves time forward
+/// 假时钟：睡眠只是让时间前进
+#[derive(Debug, Clone)]
+struct Fake {
+  start: Instant,
+  slept: Rc<Cell<Duration>>,
+}
+
+impl jdb_fs::rate_limit::Clock for Fake {
+  fn now(&self) -> Instant {
+    self.start + self.slept.get()
+  }
+
+  fn sleep(&self, dur: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+    self.slept.set(self.slept.get() + dur);
+    Box::pin(ready(()))
+  }
+}
+
+#[compio::test]
+async fn test_limit_output() {
+  use compio::io::AsyncWrite;
+  use jdb_fs::{
+    BufFile, RateLimit,
+    rate_limit::{Io, Tune},
+  };
+
+  let dir = tempfile::tempdir().unwrap();
+  let file = compio_fs::OpenOptions::new()
+    .write(true)
+    .create(true)
+    .open(dir.path().join("1.sst"))
+    .await
+    .unwrap();
+  let mut out = BufFile::new(file, 0);
+  let clock = Fake {
+    start: Instant::now(),
+    slept: Rc::default(),
+  };
+  let mut levels =
+    Levels::new(lru(), (1..=4).map(|id| meta(id, Level::L0, 10, b"a", b"z"))).unwrap();
+  let tune = Tune {
+    min: 4096,
+    max: 4096,
+    soft: 0,
+    hard: 1,
+  };
+  levels.set_rate_limit(RateLimit::with_clock(clock.clone(), 0, 0), tune);
+  levels.limit(&mut out, Io::Compact);
+
+  // The tuned rate starts with no burst banked: 12288 bytes at 4096 bytes/s take three seconds
+  // 调整后的速率起始时没有积攒突发额度：每秒 4096 字节写 12288 字节耗时三秒
+  let compio::BufResult(r, _) = out.write(vec![1u8; 12288]).await;
+  r.unwrap();
+  out.flush().await;
+  assert_eq!(clock.slept.get(), Duration::from_secs(3));
+}
+
+#[test]
+fn test_stall() {
+  use jdb_base::stall::{Reason, Stall};
+
+  let mut levels = Levels::new(
+    lru(),
+    (1..=20).map(|id| meta(id, Level::L0, 10, b"a", b"z")),
+  )
+  .unwrap();
+  assert_eq!(levels.signal.get(), Stall::Delay(Reason::L0Files));
+
+  let signal = levels.signal.clone();
+  for id in 21..=36 {
+    levels.sink.push(id, sst(Level::L0, 10));
+  }
+  levels.sink.l0_sublevels(36);
+  assert_eq!(levels.sink.stall(), Stall::Stop(Reason::L0Files));
+  // Signal follows only applied ops
+  // 信号只跟随已应用的操作
+  assert_eq!(signal.get(), Stall::Delay(Reason::L0Files));
+}
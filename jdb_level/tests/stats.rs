@@ -0,0 +1,63 @@
+use jdb_base::{
+  ckp::sst::{
+    Meta,
+    ckp::{Levels as _, Op},
+  },
+  sst::Level,
+};
+use jdb_level::{Levels, sink::SCALE};
+
+mod common;
+
+use common::lru;
+
+fn meta(id: u64, level: Level, size: u64, rmed: u64) -> Meta {
+  let mut meta = common::meta(id, level, size, b"a", b"z");
+  meta.sst.rmed = rmed;
+  meta
+}
+
+#[compio::test]
+async fn test_stats() {
+  let mut levels = Levels::new(lru(), []).unwrap();
+  for id in 1..=4 {
+    levels
+      .update(Op::Mem2SstWal {
+        meta: meta(id, Level::L0, 100, 0),
+        wal_id: id,
+      })
+      .unwrap();
+  }
+  assert_eq!(levels.wal_id, 4);
+  levels
+    .update(Op::Compact {
+      add: vec![meta(5, Level::L6, 300, 20)],
+      rm: vec![(Level::L0, vec![1, 2, 3, 4])],
+    })
+    .unwrap();
+  // Trivial move does not count as compaction I/O
+  // 平凡移动不计入压缩 I/O
+  levels
+    .update(Op::Compact {
+      add: vec![meta(5, Level::L5, 300, 20)],
+      rm: vec![(Level::L6, vec![5])],
+    })
+    .unwrap();
+
+  let stats = levels.stats();
+  assert_eq!(stats.io.flush, 400);
+  assert_eq!(stats.io.read, 400);
+  assert_eq!(stats.io.write, 300);
+  assert_eq!(stats.io.write_amp(), 1.75);
+
+  let l0 = &stats.levels[Level::L0 as usize];
+  assert_eq!(l0.files, 0);
+  let l5 = &stats.levels[Level::L5 as usize];
+  assert_eq!((l5.files, l5.size, l5.virtual_size), (1, 300, 320));
+  assert!(l5.score >= SCALE as u32);
+
+  let table = stats.to_string();
+  assert!(table.starts_with("Level"));
+  assert!(table.contains("write-amp 1.75"));
+  log::info!("\n{table}");
+}
@@ -0,0 +1,89 @@
+//! Compare memtable backends on typical key distributions
+//! 在典型键分布上比较内存表后端
+
+use std::{hint::black_box, ops::Bound};
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use jdb_base::{Flag, Pos};
+use jdb_mem::{Art, BTree, Map, Skip, Table};
+
+const N: usize = 100_000;
+
+const POS: Pos = Pos {
+  ver: 1,
+  wal_id: 1,
+  offset_or_file_id: 0,
+  len: 100,
+  flag: Flag::INFILE,
+};
+
+/// Key distributions: monotonic ids, random bytes, tenant prefixed ids
+/// 键分布：单调 id、随机字节、带租户前缀的 id
+fn keys(name: &str) -> Vec<Box<[u8]>> {
+  let mut rng = fastrand::Rng::with_seed(42);
+  (0..N as u64)
+    .map(|i| -> Box<[u8]> {
+      match name {
+        "seq" => i.to_be_bytes().into(),
+        "random" => (0..16).map(|_| rng.u8(..)).collect(),
+        _ => {
+          let mut k = format!("tenant{:04}/", rng.u16(..1000)).into_bytes();
+          k.extend_from_slice(&rng.u64(..).to_be_bytes());
+          k.into()
+        }
+      }
+    })
+    .collect()
+}
+
+fn build<T: Table>(keys: &[Box<[u8]>]) -> Map<T> {
+  let mut map = Map::new();
+  for k in keys {
+    map.put(k, POS);
+  }
+  map
+}
+
+fn bench_one<T: Table>(c: &mut Criterion, name: &str, dist: &str, keys: &[Box<[u8]>]) {
+  let mut g = c.benchmark_group(dist);
+  g.throughput(Throughput::Elements(keys.len() as u64));
+
+  g.bench_function(BenchmarkId::new("insert", name), |b| {
+    b.iter(|| black_box(build::<T>(keys)))
+  });
+
+  let map = build::<T>(keys);
+  let table = map.table();
+  g.bench_function(BenchmarkId::new("get", name), |b| {
+    b.iter(|| {
+      for k in keys {
+        black_box(table.get(k));
+      }
+    })
+  });
+
+  // Short scans of 16 entries from each of 1/16 of the keys
+  // 从 1/16 的键出发各扫描 16 个条目
+  g.bench_function(BenchmarkId::new("range", name), |b| {
+    b.iter(|| {
+      for k in keys.iter().step_by(16) {
+        for kv in table.range(Bound::Included(k), Bound::Unbounded).take(16) {
+          black_box(kv);
+        }
+      }
+    })
+  });
+  g.finish();
+}
+
+fn bench(c: &mut Criterion) {
+  for dist in ["seq", "random", "tenant"] {
+    let keys = keys(dist);
+    bench_one::<BTree>(c, "btree", dist, &keys);
+    bench_one::<Skip>(c, "skip", dist, &keys);
+    bench_one::<Art>(c, "art", dist, &keys);
+  }
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,159 @@
+//! Bump arena for memtable keys and table nodes, freed wholesale with its Map
+//! 内存表键和表节点的碰撞分配竞技场，随其 Map 整体释放
+
+use std::{alloc::Layout, borrow::Borrow, cmp::Ordering, fmt, ops::Deref, ptr::NonNull};
+
+/// Chunk size, larger allocations get a chunk of their own
+/// 块大小，更大的分配独占一个块
+pub const CHUNK: usize = 64 << 10;
+
+/// Key bytes in an arena, valid while the owning arena lives.
+/// Only the Map's arena creates them and they can't be copied.
+/// Not exported: tables are reached only through `Map`, so no key leaves it
+/// 位于竞技场中的键字节，在所属竞技场存活期间有效。
+/// 仅由 Map 的竞技场创建且不可复制。
+/// 不导出：表只能经由 `Map` 访问，因此键不会离开 Map
+pub struct Key {
+  ptr: NonNull<u8>,
+  len: usize,
+}
+
+impl Key {
+  /// Empty key without arena, used as a placeholder
+  /// 不依赖竞技场的空键，用作占位符
+  #[inline]
+  pub(crate) const fn empty() -> Self {
+    Self {
+      ptr: NonNull::dangling(),
+      len: 0,
+    }
+  }
+}
+
+impl Deref for Key {
+  type Target = [u8];
+
+  #[inline]
+  fn deref(&self) -> &[u8] {
+    // SAFETY: points into a chunk that is never moved or freed before the arena
+    // 安全：指向在竞技场释放前不会移动或释放的块
+    unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+  }
+}
+
+impl Borrow<[u8]> for Key {
+  #[inline]
+  fn borrow(&self) -> &[u8] {
+    self
+  }
+}
+
+impl PartialEq for Key {
+  #[inline]
+  fn eq(&self, other: &Self) -> bool {
+    **self == **other
+  }
+}
+
+impl Eq for Key {}
+
+impl PartialOrd for Key {
+  #[inline]
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Key {
+  #[inline]
+  fn cmp(&self, other: &Self) -> Ordering {
+    (**self).cmp(&**other)
+  }
+}
+
+impl fmt::Debug for Key {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    (**self).fmt(f)
+  }
+}
+
+/// Chunks are filled within their capacity only, so bytes never move.
+/// Nothing placed here is dropped, only the chunks are freed
+/// 块只在其容量内填充，因此字节永不移动。
+/// 放入的内容不会被 drop，只释放块本身
+#[derive(Default, Debug)]
+pub struct Arena {
+  /// Only the spare capacity is used, lengths stay 0
+  /// 只使用空闲容量，长度始终为 0
+  chunks: Vec<Vec<u8>>,
+  /// Bytes taken from the last chunk
+  /// 最后一个块已占用的字节数
+  off: usize,
+  /// Capacity of all chunks
+  /// 所有块的容量
+  size: usize,
+}
+
+impl Arena {
+  /// Uninitialized memory for layout, valid while the arena lives
+  /// 按布局分配的未初始化内存，在竞技场存活期间有效
+  pub fn alloc_layout(&mut self, layout: Layout) -> NonNull<u8> {
+    let off = self.off;
+    let fit = self.chunks.last().and_then(|c| {
+      let pad = c.as_ptr().wrapping_add(off).align_offset(layout.align());
+      (c.capacity() - off >= pad + layout.size()).then_some(off + pad)
+    });
+    let start = match fit {
+      Some(start) => start,
+      None => {
+        let chunk = Vec::<u8>::with_capacity(CHUNK.max(layout.size() + layout.align() - 1));
+        let start = chunk.as_ptr().align_offset(layout.align());
+        self.size += chunk.capacity();
+        self.chunks.push(chunk);
+        start
+      }
+    };
+    self.off = start + layout.size();
+    // Just pushed or checked above
+    // 刚推入或已在上方检查
+    let chunk = self.chunks.last_mut().unwrap();
+    // SAFETY: start + size <= capacity, within the chunk allocation
+    // 安全：start + size <= 容量，位于块分配内
+    unsafe { NonNull::new_unchecked(chunk.as_mut_ptr().add(start)) }
+  }
+
+  /// Move val into the arena, it is never dropped
+  /// 将 val 移入竞技场，它永远不会被 drop
+  #[inline]
+  pub fn put<T>(&mut self, val: T) -> NonNull<T> {
+    let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+    // SAFETY: fresh memory with the layout of T
+    // 安全：符合 T 布局的新内存
+    unsafe { ptr.write(val) };
+    ptr
+  }
+
+  /// Copy key into the arena
+  /// 将键复制进竞技场
+  pub fn alloc(&mut self, key: &[u8]) -> Key {
+    let ptr = self.alloc_layout(Layout::for_value(key));
+    // SAFETY: fresh memory of key.len() bytes
+    // 安全：key.len() 字节的新内存
+    unsafe {
+      ptr
+        .as_ptr()
+        .copy_from_nonoverlapping(key.as_ptr(), key.len())
+    };
+    Key {
+      ptr,
+      len: key.len(),
+    }
+  }
+
+  /// Bytes allocated: every chunk plus the chunk list
+  /// 已分配的字节数：所有块加上块列表
+  #[inline]
+  pub fn size(&self) -> usize {
+    self.size + self.chunks.capacity() * size_of::<Vec<u8>>()
+  }
+}
@@ -0,0 +1,105 @@
+//! Atomic write batch: entries become visible together
+//! 原子写批次：条目同时可见
+
+use bitcode::{Decode, Encode};
+use jdb_base::{Discard, Pos, sst::MemToSst};
+
+use crate::{Mem, table::Table};
+
+/// Puts and deletes applied as one unit, logged as one WAL record
+/// 作为一个整体应用的写入和删除，作为一条 WAL 记录写日志
+#[derive(Debug, Default, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct WriteBatch {
+  /// Keys back to back
+  /// 首尾相接的键
+  buf: Vec<u8>,
+  /// (key end in buf, pos)
+  /// （键在 buf 中的结束位置, pos）
+  li: Vec<(u32, Pos)>,
+}
+
+impl WriteBatch {
+  #[inline]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  #[inline]
+  pub fn put(&mut self, key: impl AsRef<[u8]>, pos: Pos) -> &mut Self {
+    self.buf.extend_from_slice(key.as_ref());
+    self.li.push((self.buf.len() as u32, pos));
+    self
+  }
+
+  /// Delete key, pos is stored as a tombstone
+  /// 删除键，pos 以墓碑形式存储
+  #[inline]
+  pub fn delete(&mut self, key: impl AsRef<[u8]>, pos: Pos) -> &mut Self {
+    self.put(key, pos.to_tombstone())
+  }
+
+  #[inline]
+  pub fn len(&self) -> usize {
+    self.li.len()
+  }
+
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.li.is_empty()
+  }
+
+  #[inline]
+  pub fn clear(&mut self) {
+    self.buf.clear();
+    self.li.clear();
+  }
+
+  /// Entries in insertion order, later ones win on equal keys
+  /// 按插入顺序的条目，键相同时后者生效
+  pub fn iter(&self) -> impl Iterator<Item = (&[u8], Pos)> {
+    let mut start = 0;
+    self.li.iter().map(move |(end, pos)| {
+      let end = *end as usize;
+      let key = &self.buf[start..end];
+      start = end;
+      (key, *pos)
+    })
+  }
+
+  /// Encode as one record for WAL
+  /// 编码为一条 WAL 记录
+  #[inline]
+  pub fn encode(&self) -> Vec<u8> {
+    bitcode::encode(self)
+  }
+
+  #[inline]
+  pub fn decode(bin: &[u8]) -> Result<Self, bitcode::Error> {
+    bitcode::decode(bin)
+  }
+}
+
+impl<F, D, T> Mem<F, D, T>
+where
+  F: MemToSst,
+  D: Discard,
+  T: Table,
+{
+  /// Apply batch atomically: rotation is checked once up front, the batch is
+  /// logged as one record, then every entry is inserted with no await in between
+  /// 原子地应用批次：预先只检查一次轮转，批次记录为一条日志，之后插入所有条目，中间没有 await
+  pub async fn write(&mut self, batch: &WriteBatch) -> Result<(), crate::Error<F::Error>> {
+    if batch.is_empty() {
+      return Ok(());
+    }
+    self.reserve().await?;
+    if let Some(wal) = &mut self.wal {
+      wal.write(batch).await.map_err(crate::Error::Wal)?;
+    }
+    for (key, pos) in batch.iter() {
+      self.now.put(key, pos);
+    }
+    self.resize();
+    Ok(())
+  }
+}
@@ -0,0 +1,190 @@
+//! Write buffer budget shared by many Mem instances on one thread
+//! 同一线程上多个 Mem 实例共享的写缓冲预算
+
+use std::{
+  cell::{Cell, RefCell},
+  future::poll_fn,
+  rc::Rc,
+  task::{Poll, Waker},
+};
+
+#[derive(Debug, Default)]
+struct Slot {
+  /// Active map size last reported
+  /// 最近上报的活跃 Map 大小
+  size: Cell<usize>,
+  /// Asked to rotate by the budget
+  /// 被预算要求轮转
+  force: Cell<bool>,
+  waker: RefCell<Option<Waker>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+  limit: Cell<usize>,
+  /// Active maps plus frozen maps not flushed yet
+  /// 活跃 Map 加上尚未刷盘的冻结 Map
+  used: Cell<usize>,
+  slots: RefCell<Vec<Rc<Slot>>>,
+}
+
+/// Shared budget over the in-memory maps of every registered Mem.
+/// Once it runs out, the largest active map is forced to rotate and flush
+/// 所有已注册 Mem 的内存中 Map 共享的预算。
+/// 预算耗尽时，强制最大的活跃 Map 轮转并刷盘
+#[derive(Debug, Clone)]
+pub struct WriteBuffer(Rc<Inner>);
+
+impl WriteBuffer {
+  #[inline]
+  pub fn new(limit: usize) -> Self {
+    Self(Rc::new(Inner {
+      limit: Cell::new(limit),
+      used: Cell::new(0),
+      slots: RefCell::default(),
+    }))
+  }
+
+  #[inline]
+  pub fn limit(&self) -> usize {
+    self.0.limit.get()
+  }
+
+  #[inline]
+  pub fn set_limit(&self, limit: usize) {
+    self.0.limit.set(limit);
+    self.check();
+  }
+
+  /// Bytes of all members' maps in memory, frozen ones count until flushed
+  /// 所有成员内存中 Map 的字节数，冻结的 Map 计入直到刷盘
+  #[inline]
+  pub fn used(&self) -> usize {
+    self.0.used.get()
+  }
+
+  /// Join the budget, assign to `Mem::buffer`
+  /// 加入预算，赋值给 `Mem::buffer`
+  pub fn register(&self) -> Member {
+    let slot = Rc::new(Slot::default());
+    self.0.slots.borrow_mut().push(slot.clone());
+    Member {
+      buf: self.clone(),
+      slot,
+    }
+  }
+
+  /// Force the largest member not yet asked to rotate, like RocksDB's `WriteBufferManager`:
+  /// when those active maps reach 7/8 of `limit`, or `used` reaches `limit` while they hold half.
+  /// Frozen maps only shrink by flushing, so alone they never force a rotate
+  /// 强制尚未被要求轮转的最大成员轮转，同 RocksDB 的 `WriteBufferManager`：
+  /// 这些活跃 Map 达到 `limit` 的 7/8，或 `used` 达到 `limit` 且它们占一半时触发。
+  /// 冻结 Map 只能靠刷盘缩小，因此单凭它们不会强制轮转
+  fn check(&self) {
+    let inner = &*self.0;
+    let (limit, used) = (inner.limit.get(), inner.used.get());
+    if used < limit - limit / 8 {
+      return;
+    }
+    let slots = inner.slots.borrow();
+    let unforced = || slots.iter().filter(|s| !s.force.get());
+    let active: usize = unforced().map(|s| s.size.get()).sum();
+    if active < limit - limit / 8 && (used < limit || active < limit / 2) {
+      return;
+    }
+    if let Some(slot) = unforced().max_by_key(|s| s.size.get())
+      && slot.size.get() > 0
+    {
+      slot.force.set(true);
+      if let Some(waker) = slot.waker.take() {
+        waker.wake();
+      }
+    }
+  }
+}
+
+/// Frozen map bytes held in the budget, released on drop with the map
+/// 预算中占用的冻结 Map 字节，随 Map 一起 drop 时释放
+#[derive(Debug)]
+pub(crate) struct Held {
+  buf: WriteBuffer,
+  size: usize,
+}
+
+impl Drop for Held {
+  fn drop(&mut self) {
+    let used = &self.buf.0.used;
+    used.set(used.get() - self.size);
+  }
+}
+
+/// One Mem's share of a `WriteBuffer`, leaves the budget on drop
+/// 一个 Mem 在 `WriteBuffer` 中的份额，drop 时退出预算
+#[derive(Debug)]
+pub struct Member {
+  buf: WriteBuffer,
+  slot: Rc<Slot>,
+}
+
+impl Member {
+  /// Report the active map size
+  /// 上报活跃 Map 大小
+  #[inline]
+  pub fn set(&self, size: usize) {
+    let inner = &*self.buf.0;
+    inner
+      .used
+      .set(inner.used.get() - self.slot.size.replace(size) + size);
+    if size > 0 {
+      self.buf.check();
+    }
+  }
+
+  /// Keep `size` frozen bytes in the budget until the returned guard drops
+  /// 在预算中保留 `size` 字节的冻结数据，直到返回的守卫被 drop
+  #[inline]
+  pub(crate) fn hold(&self, size: usize) -> Held {
+    let used = &self.buf.0.used;
+    used.set(used.get() + size);
+    Held {
+      buf: self.buf.clone(),
+      size,
+    }
+  }
+
+  /// Whether the budget asked this member to rotate, clears the request
+  /// 预算是否要求该成员轮转，并清除该请求
+  #[inline]
+  pub fn take_force(&self) -> bool {
+    self.slot.force.replace(false)
+  }
+
+  /// Wait until the budget asks this member to rotate,
+  /// the future owns its slot so the Mem is not borrowed meanwhile
+  /// 等待直到预算要求该成员轮转，
+  /// 该 future 持有自己的槽位，等待期间不借用 Mem
+  pub fn wait_force(&self) -> impl Future<Output = ()> + 'static {
+    let slot = self.slot.clone();
+    poll_fn(move |cx| {
+      if slot.force.get() {
+        Poll::Ready(())
+      } else {
+        *slot.waker.borrow_mut() = Some(cx.waker().clone());
+        Poll::Pending
+      }
+    })
+  }
+}
+
+impl Drop for Member {
+  fn drop(&mut self) {
+    self.take_force();
+    self.set(0);
+    self
+      .buf
+      .0
+      .slots
+      .borrow_mut()
+      .retain(|s| !Rc::ptr_eq(s, &self.slot));
+  }
+}
@@ -5,7 +5,7 @@ use jdb_base::{Discard, sst::MemToSst};
 use log::error;
 
 use super::{Disk, FlushResult};
-use crate::Map;
+use crate::{Map, table::Table};
 
 /// Start background flush operations.
 /// Note on Design (Rc/RefCell + compio):
@@ -28,10 +28,11 @@ use crate::Map;
 /// 当刷盘正在运行（等待 I/O）时，我们持有 Disk 的可变借用。
 /// 这确保了独占访问：没有其他任务可以并发修改 Disk。
 /// `State` 状态机保证我们在 `Step::Ing` 时不会尝试启动另一个刷盘或访问 Disk。
-pub fn run<S, D>(disk: Rc<RefCell<Disk<S, D>>>, map: Rc<Map>) -> Receiver<FlushResult<S>>
+pub fn run<S, D, T>(disk: Rc<RefCell<Disk<S, D>>>, map: Rc<Map<T>>) -> Receiver<FlushResult<S>>
 where
   S: MemToSst,
   D: Discard,
+  T: Table,
 {
   let (tx, rx) = channel();
   compio::runtime::spawn(
@@ -44,12 +45,22 @@ where
         let mut disk = disk.borrow_mut();
         let Disk { sst, discard } = &mut *disk;
 
-        // Process discarded entries before flushing
-        // 在刷盘前处理丢弃的条目
-        for (k, p) in &map.discards {
-          discard.discard(k, p);
-        }
-        futures::join!(sst.write(map.iter()), discard.flush())
+        let sst_res = sst.write(map.iter(), map.range_dels()).await;
+        // Report garbage only once the SST holds the map,
+        // a failed flush is retried and would count it again
+        // 仅在 SST 写入该 Map 后才上报垃圾，失败的刷盘会重试，提前上报会重复计数
+        let discard_res = if sst_res.is_ok() {
+          for (wal_id, end) in map.ends() {
+            discard.wal_size(wal_id, end);
+          }
+          for (k, p) in map.discards() {
+            discard.discard(k, &p);
+          }
+          discard.flush().await
+        } else {
+          Ok(())
+        };
+        (sst_res, discard_res)
       };
 
       let (sst_res, discard_res) = res;
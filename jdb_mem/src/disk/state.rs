@@ -1,11 +1,11 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
 
 use futures::channel::oneshot::Receiver;
 use jdb_base::{Discard, ckp::sst::Meta, sst::MemToSst};
 use log::error;
 
 use super::{Disk, FlushResult, run::run};
-use crate::{Error, Map};
+use crate::{Error, Map, table::Table};
 
 /// Flush task state enum
 /// 刷盘任务状态枚举
@@ -52,12 +52,13 @@ where
     }
   }
 
-  /// Complete flush: push sst then remove freeze (sync, no await)
-  /// 完成刷盘：先 push sst 再删除 freeze（同步，无 await）
+  /// Complete flush: push sst then remove the oldest freeze (sync, no await)
+  /// 完成刷盘：先 push sst 再删除最老的 freeze（同步，无 await）
   #[inline]
-  fn done(&mut self, meta: Meta, freeze: &mut Option<Rc<Map>>) {
-    self.disk.borrow_mut().sst.push(meta);
-    *freeze = None;
+  fn done<T: Table>(&mut self, meta: Meta, freeze: &mut VecDeque<Rc<Map<T>>>) {
+    if let Some(map) = freeze.pop_front() {
+      self.disk.borrow_mut().sst.push(meta, map.wal_id);
+    }
     self.step = Step::<S>::Idle;
   }
 
@@ -72,9 +73,12 @@ where
     self.step = Step::<S>::Idle;
   }
 
-  /// Check task status and trigger new flush if needed
-  /// 检查任务状态并在需要时触发新的刷盘
-  pub fn flush(&mut self, freeze: &mut Option<Rc<Map>>) -> Result<(), Error<S::Error>> {
+  /// Check task status and trigger new flush if needed, oldest freeze first
+  /// 检查任务状态并在需要时触发新的刷盘，最老的 freeze 优先
+  pub fn flush<T: Table>(
+    &mut self,
+    freeze: &mut VecDeque<Rc<Map<T>>>,
+  ) -> Result<(), Error<S::Error>> {
     loop {
       match &mut self.step {
         Step::Ing(rx) => {
@@ -99,7 +103,7 @@ where
           }
         }
         Step::Idle => {
-          if let Some(map) = freeze {
+          if let Some(map) = freeze.front() {
             let map = map.clone();
             let rx = run(self.disk.clone(), map);
             self.step = Step::Ing(rx);
@@ -113,7 +117,10 @@ where
 
   /// Block and wait for active flush to complete
   /// 阻塞并等待当前刷盘完成
-  pub async fn wait(&mut self, freeze: &mut Option<Rc<Map>>) -> Result<(), Error<S::Error>> {
+  pub async fn wait<T: Table>(
+    &mut self,
+    freeze: &mut VecDeque<Rc<Map<T>>>,
+  ) -> Result<(), Error<S::Error>> {
     // Ensure task is running
     // 确保任务正在运行
     self.flush(freeze)?;
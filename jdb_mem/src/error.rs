@@ -13,6 +13,12 @@ pub enum Error<SstError: Debug> {
   /// Background flush task disconnected
   /// 后台刷盘任务断开连接
   Disconnect,
+  /// WAL append or replay error
+  /// WAL 追加或回放错误
+  Wal(jdb_fs::Error),
+  /// Flush retries exhausted, writes fail until restart, cause in `Mem::bg_error`
+  /// 刷盘重试已耗尽，重启前写入都会失败，原因见 `Mem::bg_error`
+  ReadOnly,
 }
 
 impl<SstError: Debug> std::fmt::Display for Error<SstError> {
@@ -20,6 +26,8 @@ impl<SstError: Debug> std::fmt::Display for Error<SstError> {
     match self {
       Self::Sst(e) => write!(f, "SST error: {:?}", e),
       Self::Disconnect => write!(f, "Flush task disconnected"),
+      Self::Wal(e) => write!(f, "WAL error: {e}"),
+      Self::ReadOnly => write!(f, "Read-only after background error"),
     }
   }
 }
@@ -0,0 +1,186 @@
+//! Dead bytes per WAL file, fed by memtable discards, for value-log GC
+//! 每个 WAL 文件的死字节数，由内存表丢弃项提供，供值日志 GC 使用
+
+use std::{
+  collections::{BTreeMap, BTreeSet},
+  path::PathBuf,
+};
+
+use jdb_base::{Discard, Pos};
+use jdb_fs::{AutoCompact, Compact, DataLen, Error, IncrCount, Item};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+/// Totals of one WAL file, the latest record wins on load
+/// 单个 WAL 文件的累计值，加载时最新记录生效
+#[derive(Debug, Clone, Copy, Default, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned)]
+#[repr(C, packed)]
+pub struct Head {
+  pub wal_id: u64,
+  /// Bytes of overwritten or deleted values
+  /// 被覆盖或删除的值的字节数
+  pub dead: u64,
+  /// File size, 0 while unknown
+  /// 文件大小，未知时为 0
+  pub size: u64,
+  /// Non-zero marks a removed file
+  /// 非零表示文件已删除
+  pub removed: u8,
+}
+
+impl DataLen for Head {
+  #[inline]
+  fn data_len(&self) -> usize {
+    0
+  }
+}
+
+#[derive(Debug, Default)]
+struct Totals(BTreeMap<u64, Head>);
+
+impl Item for Totals {
+  const MAGIC: u8 = 0x47;
+  type Head = Head;
+}
+
+impl Compact for Totals {
+  fn on_head(&mut self, head: Head) -> IncrCount {
+    let wal_id = head.wal_id;
+    if head.removed != 0 {
+      self.0.remove(&wal_id);
+    } else {
+      self.0.insert(wal_id, head);
+    }
+    true
+  }
+
+  fn rewrite(&self) -> impl Iterator<Item = &Head> {
+    self.0.values()
+  }
+}
+
+/// `Discard` that sums dead value bytes per `Pos.wal_id`, persisted on every flush
+/// 按 `Pos.wal_id` 累计死值字节数的 `Discard`，每次刷盘时持久化
+pub struct Garbage {
+  log: AutoCompact<Totals>,
+  /// WAL ids changed since the last flush
+  /// 上次刷盘后变化的 WAL id
+  dirty: BTreeSet<u64>,
+}
+
+impl Garbage {
+  /// Open or create the totals log at `path`
+  /// 在 `path` 打开或创建累计值日志
+  pub async fn open(path: impl Into<PathBuf>) -> jdb_fs::Result<Self> {
+    Ok(Self {
+      log: AutoCompact::open(path).await?,
+      dirty: BTreeSet::new(),
+    })
+  }
+
+  #[inline]
+  fn entry(&mut self, wal_id: u64) -> &mut Head {
+    self.dirty.insert(wal_id);
+    self.log.inner.0.entry(wal_id).or_insert(Head {
+      wal_id,
+      ..Head::default()
+    })
+  }
+
+  /// Record the size of a WAL file, needed for its ratio
+  /// 记录 WAL 文件大小，计算比例时需要
+  #[inline]
+  pub fn set_size(&mut self, wal_id: u64, size: u64) {
+    self.entry(wal_id).size = size;
+  }
+
+  /// Forget a WAL file once value-log GC removed it
+  /// 值日志 GC 删除 WAL 文件后将其遗忘
+  #[inline]
+  pub fn rm(&mut self, wal_id: u64) {
+    if self.log.inner.0.remove(&wal_id).is_some() {
+      self.dirty.insert(wal_id);
+    }
+  }
+
+  /// Totals of a WAL file, None if untracked
+  /// WAL 文件的累计值，未跟踪时为 None
+  #[inline]
+  pub fn get(&self, wal_id: u64) -> Option<&Head> {
+    self.log.inner.0.get(&wal_id)
+  }
+
+  /// Dead bytes of a WAL file
+  /// WAL 文件的死字节数
+  #[inline]
+  pub fn dead(&self, wal_id: u64) -> u64 {
+    self.log.inner.0.get(&wal_id).map_or(0, |h| h.dead)
+  }
+
+  /// Dead bytes / file size, None if the size is unknown
+  /// 死字节数 / 文件大小，大小未知时为 None
+  #[inline]
+  pub fn garbage_ratio(&self, wal_id: u64) -> Option<f64> {
+    ratio(self.log.inner.0.get(&wal_id)?)
+  }
+
+  /// WAL file with the highest garbage ratio
+  /// 垃圾比例最高的 WAL 文件
+  pub fn dirtiest(&self) -> Option<(u64, f64)> {
+    self
+      .log
+      .inner
+      .0
+      .values()
+      .filter_map(|h| Some((h.wal_id, ratio(h)?)))
+      .max_by(|a, b| a.1.total_cmp(&b.1))
+  }
+}
+
+#[inline]
+fn ratio(head: &Head) -> Option<f64> {
+  let size = head.size;
+  (size > 0).then(|| head.dead as f64 / size as f64)
+}
+
+impl Discard for Garbage {
+  type Error = Error;
+
+  /// Only values inside WAL files count, separate value files are not tracked here
+  /// 只统计 WAL 文件内的值，独立值文件不在此跟踪
+  #[inline]
+  fn discard(&mut self, _key: &[u8], pos: &Pos) {
+    if pos.flag.is_infile() {
+      self.entry(pos.wal_id).dead += pos.len as u64;
+    }
+  }
+
+  /// Only grows, a memtable may have seen just part of the file
+  /// 只增不减，内存表可能只见到文件的一部分
+  #[inline]
+  fn wal_size(&mut self, wal_id: u64, size: u64) {
+    let head = self.entry(wal_id);
+    head.size = head.size.max(size);
+  }
+
+  async fn flush(&mut self) -> Result<(), Self::Error> {
+    if self.dirty.is_empty() {
+      return Ok(());
+    }
+    let li: Vec<Head> = std::mem::take(&mut self.dirty)
+      .into_iter()
+      .map(|wal_id| {
+        self.log.inner.0.get(&wal_id).copied().unwrap_or(Head {
+          wal_id,
+          removed: 1,
+          ..Head::default()
+        })
+      })
+      .collect();
+    self
+      .log
+      .push_iter(li.into_iter().map(|head| (head, (), true)))
+      .await?;
+    self.log.maybe_compact().await?;
+    self.log.sync().await
+  }
+}
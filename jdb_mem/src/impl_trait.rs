@@ -1,4 +1,4 @@
-use core::ops::RangeBounds;
+use core::ops::{Range, RangeBounds};
 use std::borrow::Borrow;
 
 use jdb_base::{
@@ -9,34 +9,43 @@ use jdb_base::{
 };
 
 use crate::{
-  Map, Mem,
-  iter::{MapIter, MapRevIter, Merge2Iter, Merged},
+  Mem,
+  iter::{Masked, Merge2Iter, MergeIter, Merged},
+  table::Table,
 };
 
-/// Collect iterators from all map layers (now -> freeze)
-/// 从所有 Map 层级收集迭代器（now -> freeze）
+/// Collect iterators from all map layers (now -> freeze, newest first),
+/// masked by the range tombstones of every layer
+/// 从所有 Map 层级收集迭代器（now -> freeze，由新到旧），并用各层范围墓碑遮蔽
 macro_rules! iter {
-  ($self:expr, $method:ident) => {{
-    let now = $self.now.$method();
-    match &$self.freeze {
-      Some(m) => Merged::Two(Merge2Iter::new(now, m.$method())),
-      None => Merged::One(now),
-    }
-  }};
-
-  ($self:expr, $method:ident, $bounds:expr) => {{
-    let now = $self.now.$method::<[u8]>($bounds);
-    match &$self.freeze {
-      Some(m) => Merged::Two(Merge2Iter::new(now, m.$method::<[u8]>($bounds))),
-      None => Merged::One(now),
-    }
+  ($self:expr, $m:ident => $call:expr) => {{
+    let now = {
+      let $m = &$self.now;
+      $call
+    };
+    let range_del = std::iter::once(&$self.now)
+      .chain($self.freeze.iter().map(|m| &**m))
+      .flat_map(|m| m.range_dels())
+      .collect();
+    let iter = match $self.freeze.len() {
+      0 => Merged::One(now),
+      1 => {
+        let $m = &$self.freeze[0];
+        Merged::Two(Merge2Iter::new(now, $call))
+      }
+      _ => Merged::Many(MergeIter::new(
+        std::iter::once(now).chain($self.freeze.iter().rev().map(|$m| $call)),
+      )),
+    };
+    Masked { iter, range_del }
   }};
 }
 
-impl<F, D> jdb_base::Mem for Mem<F, D>
+impl<F, D, T> jdb_base::Mem for Mem<F, D, T>
 where
   F: MemToSst,
   D: Discard,
+  T: Table,
 {
   type Error = crate::Error<F::Error>;
 
@@ -50,44 +59,54 @@ where
   /// Forward iterator type
   /// 正向迭代器类型
   type Iter<'a>
-    = Merged<'a, MapIter<'a>, Asc>
+    = Masked<'a, Merged<'a, T::Iter<'a>, Asc>>
   where
     Self: 'a;
 
   /// Reverse iterator type
   /// 反向迭代器类型
   type RevIter<'a>
-    = Merged<'a, MapRevIter<'a>, Desc>
+    = Masked<'a, Merged<'a, T::RevIter<'a>, Desc>>
   where
     Self: 'a;
 
   /// Insert a key-position pair into the active map with size tracking
   /// 将键值位置对插入活跃 Map，并跟踪大小
   #[inline]
-  async fn put(&mut self, key: impl Into<Box<[u8]>>, pos: Pos) -> Result<(), Self::Error> {
-    if self.size >= self.rotate_size {
-      // 如果需要轮转，等待挂起的刷盘完成，然后轮转，内联同步判断，可以减少await的开销
-      if self.freeze.is_some() {
-        self.wait_freeze().await?;
-      }
-      self.rotate()?;
+  async fn put(&mut self, key: impl Borrow<[u8]>, pos: Pos) -> Result<(), Self::Error> {
+    self.reserve().await?;
+    if let Some(wal) = &mut self.wal {
+      wal
+        .put(key.borrow(), pos)
+        .await
+        .map_err(crate::Error::Wal)?;
     }
-    let key = key.into();
-    // Always add size even for overwrites: discards grows on overwrite
-    // 即使是覆盖也要增加 size：覆盖时 discards 会增长，占用内存
-    self.size += key.len() + Pos::SIZE + Map::ENTRY_OVERHEAD;
-
-    // Optimize for overwrite: use get_mut to avoid tree structural changes and cloning
-    // 优化覆盖操作：使用 get_mut 避免树结构变更和克隆
-    if let Some(val) = self.now.inner.get_mut(&key) {
-      let old_pos = *val;
-      *val = pos;
-      // Zero allocation: reuse the key passed in
-      // 零分配：重用传入的键
-      self.now.discards.push((key, old_pos));
-    } else {
-      self.now.inner.insert(key, pos);
+    // Overwrites count too: discards grows on overwrite
+    // 覆盖也计入：覆盖时 discards 会增长
+    self.now.put(key.borrow(), pos);
+    self.resize();
+
+    Ok(())
+  }
+
+  /// Record a range tombstone in the active map with size tracking
+  /// 在活跃 Map 中记录范围墓碑，并跟踪大小
+  #[inline]
+  async fn delete_range<K: Borrow<[u8]>>(
+    &mut self,
+    range: Range<K>,
+    pos: Pos,
+  ) -> Result<(), Self::Error> {
+    self.reserve().await?;
+    let (start, end) = (range.start.borrow(), range.end.borrow());
+    if let Some(wal) = &mut self.wal {
+      wal
+        .delete_range(start, end, pos)
+        .await
+        .map_err(crate::Error::Wal)?;
     }
+    self.now.delete_range(start, end, pos);
+    self.resize();
 
     Ok(())
   }
@@ -95,7 +114,7 @@ where
   /// Wait for all background tasks to complete
   /// 等待所有后台任务完成
   async fn sync(&mut self) -> Result<(), Self::Error> {
-    self.wait_freeze().await
+    self.wait_freeze(0).await
   }
 
   /// Get position by key across all layers (newest first)
@@ -103,37 +122,35 @@ where
   #[inline]
   fn get(&self, key: impl Borrow<[u8]>) -> Option<Pos> {
     let key = key.borrow();
-    // Iterate: now -> freeze
+    // Iterate: now -> freeze (newest first).
+    // A layer's range tombstone hides every older layer, so the first hit wins
+    // 迭代：now -> freeze（由新到旧）。
+    // 某层的范围墓碑会隐藏所有更老的层，因此第一个命中即为结果
     if let Some(pos) = self.now.get(key) {
       return Some(pos);
     }
-    if let Some(map) = &self.freeze
-      && let Some(pos) = map.get(key)
-    {
-      return Some(pos);
-    }
-    None
+    self.freeze.iter().rev().find_map(|map| map.get(key))
   }
 
   /// Get forward iterator over all layers
   /// 获取涵盖所有层级的正向迭代器
   #[inline]
   fn iter(&self) -> Self::Iter<'_> {
-    iter!(self, iter)
+    iter!(self, m => m.iter())
   }
 
   /// Get reverse iterator over all layers
   /// 获取涵盖所有层级的反向迭代器
   #[inline]
   fn rev_iter(&self) -> Self::RevIter<'_> {
-    iter!(self, rev_iter)
+    iter!(self, m => m.rev_iter())
   }
 
   /// Get range iterator over all layers
   /// 获取涵盖所有层级的范围迭代器
   #[inline]
   fn range<Q: ?Sized + Borrow<[u8]>>(&self, range: impl RangeBounds<Q>) -> Self::Iter<'_> {
-    iter!(self, range, start_end(&range))
+    iter!(self, m => m.range::<[u8]>(start_end(&range)))
   }
 
   /// Get reverse range iterator over all layers
@@ -145,6 +162,6 @@ where
   /// 例如：`rev_range("a".."z")` 会产出 "y", "x" ... "a"。
   #[inline]
   fn rev_range<Q: ?Sized + Borrow<[u8]>>(&self, range: impl RangeBounds<Q>) -> Self::RevIter<'_> {
-    iter!(self, rev_range, start_end(&range))
+    iter!(self, m => m.rev_range::<[u8]>(start_end(&range)))
   }
 }
@@ -1,11 +1,10 @@
 //! Iterator types for memory table
 //! 内存表的迭代器类型
 
-use std::collections::btree_map;
+use jdb_base::{Pos, order::Order, sst::RangeDel};
 
-use jdb_base::{Pos, order::Order};
-
-pub use crate::merge::Merge2Iter;
+use crate::map::covering;
+pub use crate::merge::{Merge2Iter, MergeIter};
 
 pub enum Merged<'a, I, O: Order>
 where
@@ -13,6 +12,7 @@ where
 {
   One(I),
   Two(Merge2Iter<'a, I, O>),
+  Many(MergeIter<'a, I, O>),
 }
 
 impl<'a, I, O: Order> Iterator for Merged<'a, I, O>
@@ -26,27 +26,36 @@ where
     match self {
       Self::One(iter) => iter.next(),
       Self::Two(iter) => iter.next(),
+      Self::Many(iter) => iter.next(),
     }
   }
 }
 
-macro_rules! map_iter {
-  ($name:ident, $inner:ty) => {
-    pub struct $name<'a>(pub $inner);
+/// Turn entries covered by a newer range tombstone into that tombstone,
+/// so older layers and SSTs below stay hidden
+/// 将被更新范围墓碑覆盖的条目替换为该墓碑，使更老的层和下方的 SST 保持隐藏
+pub struct Masked<'a, I> {
+  pub(crate) iter: I,
+  /// Range tombstones of all layers
+  /// 所有层的范围墓碑
+  pub(crate) range_del: Vec<RangeDel<'a>>,
+}
 
-    impl<'a> Iterator for $name<'a> {
-      type Item = (&'a [u8], Pos);
+impl<'a, I> Iterator for Masked<'a, I>
+where
+  I: Iterator<Item = (&'a [u8], Pos)>,
+{
+  type Item = (&'a [u8], Pos);
 
-      #[inline]
-      fn next(&mut self) -> Option<Self::Item> {
-        self.0.next().map(|(k, v)| (k.as_ref(), *v))
-      }
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    let (key, pos) = self.iter.next()?;
+    if !self.range_del.is_empty()
+      && let Some(del) = covering(self.range_del.iter().copied(), key)
+      && del.ver > pos.ver
+    {
+      return Some((key, del));
     }
-  };
+    Some((key, pos))
+  }
 }
-
-map_iter!(MapIter, btree_map::Range<'a, Box<[u8]>, Pos>);
-map_iter!(
-  MapRevIter,
-  std::iter::Rev<btree_map::Range<'a, Box<[u8]>, Pos>>
-);
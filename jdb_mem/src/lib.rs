@@ -1,14 +1,28 @@
 //! Memory table implementation for JDB
 //! JDB 的内存表实现
 
+mod arena;
+mod batch;
+mod buffer;
+mod garbage;
 mod impl_trait;
 mod iter;
 mod map;
 mod mem;
 mod merge;
+mod retry;
+pub mod table;
+mod wal;
 
+use arena::Key;
+pub use batch::WriteBatch;
+pub use buffer::{Member, WriteBuffer};
+pub use garbage::Garbage;
 pub use map::Map;
-pub use mem::Mem;
+pub use mem::{MAX_FREEZE, Mem, OnRetry};
+pub use retry::{Retry, RetryEvent};
+pub use table::{Art, BTree, Skip, Table};
+pub use wal::Wal;
 
 mod disk;
 mod error;
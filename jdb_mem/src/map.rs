@@ -1,64 +1,208 @@
 use core::ops::RangeBounds;
-use std::{borrow::Borrow, collections::BTreeMap};
+use std::borrow::Borrow;
 
-use jdb_base::{Pos, query::start_end};
+use jdb_base::{Pos, query::start_end, sst::RangeDel};
 
-use crate::iter::{MapIter, MapRevIter};
+use crate::{
+  Key,
+  arena::Arena,
+  buffer::Held,
+  table::{BTree, Table},
+};
 
-/// In-memory table implementation using BTreeMap
-/// 使用 BTreeMap 实现的内存表
+/// In-memory table over a pluggable ordered storage (B+tree by default).
+/// Keys and table nodes live in the Map's arena and are freed together with it after flush
+/// 基于可插拔有序存储的内存表（默认 B+ 树）。
+/// 键和表节点存放在 Map 的竞技场中，刷盘后随 Map 一起释放
 #[derive(Default, Debug)]
-pub struct Map {
-  /// Internal storage for key-position pairs
-  /// 键值位置对的内部存储
-  pub inner: BTreeMap<Box<[u8]>, Pos>,
+pub struct Map<T = BTree> {
+  /// Internal storage for key-position pairs, private so keys can't outlive the arena
+  /// 键值位置对的内部存储，私有以免键活得比竞技场久
+  inner: T,
   /// List of discarded entries for future SST GC
   /// 丢弃条目列表，用于未来的 SST 垃圾回收
-  pub discards: Vec<(Box<[u8]>, Pos)>,
+  pub(crate) discards: Vec<(Key, Pos)>,
+  /// Range tombstones [start, end), in write order
+  /// 范围墓碑 [start, end)，按写入顺序
+  range_del: Vec<(Key, Key, Pos)>,
+  /// Log holding this map's writes, 0 without WAL
+  /// 保存该 Map 写入的日志，无 WAL 时为 0
+  pub wal_id: u64,
+  /// Furthest value end per value file (`Pos.wal_id`), handed to `Discard::wal_size` on flush
+  /// 每个值文件（`Pos.wal_id`）中最远的值末尾，刷盘时交给 `Discard::wal_size`
+  pub(crate) ends: Vec<(u64, u64)>,
+  /// Share of the write buffer while frozen, released when the flushed map drops
+  /// 冻结期间占用的写缓冲份额，已刷盘的 Map drop 时释放
+  pub(crate) held: Option<Held>,
+  arena: Arena,
 }
 
-impl Map {
-  pub const ENTRY_OVERHEAD: usize = 32;
+impl<T: Table> Map<T> {
+  /// Bytes of one discards slot
+  /// discards 中一个槽位的字节数
+  pub const ENTRY_SIZE: usize = std::mem::size_of::<(Key, Pos)>();
 
   /// Create a new empty Map
   /// 创建一个新的空 Map
   #[inline]
   pub fn new() -> Self {
-    Self::default()
+    Self {
+      inner: T::default(),
+      discards: Vec::new(),
+      range_del: Vec::new(),
+      wal_id: 0,
+      ends: Vec::new(),
+      held: None,
+      arena: Arena::default(),
+    }
   }
 
-  /// Get position by key
-  /// 通过键获取位置
+  /// Insert, the overwritten entry goes to discards
+  /// 插入，被覆盖的条目进入 discards
+  #[inline]
+  pub fn put(&mut self, key: &[u8], pos: Pos) {
+    if pos.flag.is_infile() {
+      self.grow(pos.wal_id, pos.offset_or_file_id + pos.len as u64);
+    }
+    let key = self.arena.alloc(key);
+    if let Some(old) = self.inner.insert(&mut self.arena, key, pos) {
+      self.discards.push(old);
+    }
+  }
+
+  /// Values go to one or two files at a time, a short list beats a map
+  /// 值同时只写入一两个文件，短列表优于映射
+  #[inline]
+  fn grow(&mut self, wal_id: u64, end: u64) {
+    match self.ends.iter_mut().find(|(id, _)| *id == wal_id) {
+      Some((_, max)) => *max = (*max).max(end),
+      None => self.ends.push((wal_id, end)),
+    }
+  }
+
+  /// Furthest value end seen per value file
+  /// 每个值文件中见到的最远值末尾
+  #[inline]
+  pub fn ends(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+    self.ends.iter().copied()
+  }
+
+  /// Bytes of one range tombstone slot
+  /// 一个范围墓碑槽位的字节数
+  pub const RANGE_DEL_SIZE: usize = std::mem::size_of::<(Key, Key, Pos)>();
+
+  /// Delete keys in [start, end) older than pos.ver
+  /// 删除 [start, end) 中早于 pos.ver 的键
+  #[inline]
+  pub fn delete_range(&mut self, start: &[u8], end: &[u8], pos: Pos) {
+    let start = self.arena.alloc(start);
+    let end = self.arena.alloc(end);
+    self.range_del.push((start, end, pos.to_tombstone()));
+  }
+
+  /// Range tombstones, flushed to SST alongside entries
+  /// 范围墓碑，与条目一起刷入 SST
+  #[inline]
+  pub fn range_dels(&self) -> impl Iterator<Item = RangeDel<'_>> {
+    self.range_del.iter().map(|(s, e, p)| (&**s, &**e, *p))
+  }
+
+  /// Newest range tombstone covering key.
+  /// Linear scan: range deletes are rare within one memtable
+  /// 覆盖该键的最新范围墓碑。
+  /// 线性扫描：单个内存表内的范围删除很少
+  #[inline]
+  pub fn covering(&self, key: &[u8]) -> Option<Pos> {
+    covering(self.range_dels(), key)
+  }
+
+  /// Heap bytes held: arena chunks, table nodes outside the arena and the list capacities
+  /// 持有的堆字节数：竞技场块、竞技场外的表节点以及各列表的容量
+  #[inline]
+  pub fn size(&self) -> usize {
+    self.arena.size()
+      + self.inner.heap()
+      + self.discards.capacity() * Self::ENTRY_SIZE
+      + self.range_del.capacity() * Self::RANGE_DEL_SIZE
+      + self.ends.capacity() * size_of::<(u64, u64)>()
+  }
+
+  /// Nothing written: no entry or range tombstone
+  /// 未写入任何内容：无条目或范围墓碑
+  #[inline]
+  pub fn is_empty(&self) -> bool {
+    self.inner.is_empty() && self.range_del.is_empty()
+  }
+
+  /// Overwritten entries, handed to Discard on flush
+  /// 被覆盖的条目，刷盘时交给 Discard
+  #[inline]
+  pub fn discards(&self) -> impl Iterator<Item = (&[u8], Pos)> {
+    self.discards.iter().map(|(k, p)| (&**k, *p))
+  }
+
+  /// Read-only view of the storage
+  /// 存储的只读视图
+  #[inline]
+  pub fn table(&self) -> &T {
+    &self.inner
+  }
+
+  /// Get position by key, a newer covering range tombstone wins
+  /// 通过键获取位置，覆盖它的更新范围墓碑优先
   #[inline]
   pub fn get(&self, key: impl Borrow<[u8]>) -> Option<Pos> {
-    self.inner.get(key.borrow()).copied()
+    let key = key.borrow();
+    let pos = self.inner.get(key);
+    if self.range_del.is_empty() {
+      return pos;
+    }
+    match (pos, self.covering(key)) {
+      (Some(pos), Some(del)) if del.ver > pos.ver => Some(del),
+      (pos, del) => pos.or(del),
+    }
   }
 
   /// Get forward iterator
   /// 获取正向迭代器
   #[inline]
-  pub fn iter(&self) -> MapIter<'_> {
-    MapIter(self.inner.range::<[u8], _>(..))
+  pub fn iter(&self) -> T::Iter<'_> {
+    self
+      .inner
+      .range(std::ops::Bound::Unbounded, std::ops::Bound::Unbounded)
   }
 
   /// Get reverse iterator
   /// 获取反向迭代器
   #[inline]
-  pub fn rev_iter(&self) -> MapRevIter<'_> {
-    MapRevIter(self.inner.range::<[u8], _>(..).rev())
+  pub fn rev_iter(&self) -> T::RevIter<'_> {
+    self
+      .inner
+      .rev_range(std::ops::Bound::Unbounded, std::ops::Bound::Unbounded)
   }
 
   /// Get range iterator
   /// 获取范围迭代器
   #[inline]
-  pub fn range<Q: ?Sized + Borrow<[u8]>>(&self, range: impl RangeBounds<Q>) -> MapIter<'_> {
-    MapIter(self.inner.range::<[u8], _>(start_end(&range)))
+  pub fn range<Q: ?Sized + Borrow<[u8]>>(&self, range: impl RangeBounds<Q>) -> T::Iter<'_> {
+    let (start, end) = start_end(&range);
+    self.inner.range(start, end)
   }
 
   /// Get reverse range iterator
   /// 获取反向范围迭代器
   #[inline]
-  pub fn rev_range<Q: ?Sized + Borrow<[u8]>>(&self, range: impl RangeBounds<Q>) -> MapRevIter<'_> {
-    MapRevIter(self.inner.range::<[u8], _>(start_end(&range)).rev())
+  pub fn rev_range<Q: ?Sized + Borrow<[u8]>>(&self, range: impl RangeBounds<Q>) -> T::RevIter<'_> {
+    let (start, end) = start_end(&range);
+    self.inner.rev_range(start, end)
   }
 }
+
+/// Newest range tombstone in `li` covering key
+/// `li` 中覆盖该键的最新范围墓碑
+#[inline]
+pub(crate) fn covering<'a>(li: impl Iterator<Item = RangeDel<'a>>, key: &[u8]) -> Option<Pos> {
+  li.filter(|(start, end, _)| *start <= key && key < *end)
+    .map(|(_, _, pos)| pos)
+    .max_by_key(|pos| pos.ver)
+}
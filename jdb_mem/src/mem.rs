@@ -1,29 +1,85 @@
-use std::rc::Rc;
+use std::{
+  collections::VecDeque,
+  future::Future,
+  path::PathBuf,
+  pin::pin,
+  rc::Rc,
+  time::{Duration, Instant},
+};
 
-use jdb_base::{Discard, sst::MemToSst};
+use jdb_base::{
+  Discard,
+  sst::MemToSst,
+  stall::{Signal, Stall},
+};
 use log::error;
 
-use crate::{Disk, Map, disk::State};
+use crate::{
+  Disk, Error, Map, Member, Retry, RetryEvent, Wal,
+  disk::State,
+  table::{BTree, Table},
+};
+
+/// Delay per write while stall is `Delay`
+/// 停顿状态为 `Delay` 时每次写入的延迟
+const STALL_DELAY: Duration = Duration::from_millis(1);
+
+/// Default max frozen maps waiting for flush
+/// 默认等待刷盘的最大冻结 Map 数
+pub const MAX_FREEZE: usize = 4;
+
+/// Observer of failed flushes, see `Mem::on_retry`
+/// 失败刷盘的观察者，见 `Mem::on_retry`
+pub type OnRetry<E> = Box<dyn Fn(RetryEvent<'_, Error<E>>)>;
 
 /// Memory-resident part of the database with layered maps
 /// 数据库的内存储存部分，具有分层映射
-pub struct Mem<F, D>
+pub struct Mem<F, D, T = BTree>
 where
   F: MemToSst,
   D: Discard + 'static,
+  T: Table,
 {
   /// Current active map for writes
   /// 当前用于写入的活跃 Map
-  pub now: Map,
-  /// The frozen map, currently being flushed
-  /// 冻结 Map，当前正在刷盘
-  pub freeze: Option<Rc<Map>>,
-  /// Total size of keys and values in the current active map
-  /// 当前活跃 Map 中键和值的总大小
+  pub now: Map<T>,
+  /// Frozen maps waiting for flush, oldest first, flushed in order
+  /// 等待刷盘的冻结 Map，最老的在前，按顺序刷盘
+  pub freeze: VecDeque<Rc<Map<T>>>,
+  /// Max frozen maps before writes wait for flush
+  /// (like `max_write_buffer_number` minus the active map)
+  /// 写入等待刷盘前的最大冻结 Map 数（相当于 `max_write_buffer_number` 减去活跃 Map）
+  pub max_freeze: usize,
+  /// Memory held by the current active map (`Map::size`)
+  /// 当前活跃 Map 占用的内存（`Map::size`）
   pub size: usize,
   /// Maximum size before rotating the current map to old
   /// 轮转当前 Map 之前的最大大小
   pub rotate_size: usize,
+  /// Maximum age of the active map's first write before rotating, None never
+  /// 活跃 Map 首次写入后轮转前的最大存活时间，None 表示不限
+  pub max_age: Option<Duration>,
+  /// First write into the active map, None while it is empty
+  /// 活跃 Map 的首次写入时间，为空时为 None
+  pub born: Option<Instant>,
+  /// Write stall signal, replace with `Levels::signal` to follow compaction
+  /// 写入停顿信号，替换为 `Levels::signal` 以跟随压缩状态
+  pub signal: Signal,
+  /// Backoff for failed flushes
+  /// 刷盘失败的退避策略
+  pub retry: Retry,
+  /// Called on every failed flush with what the policy does next
+  /// 每次刷盘失败时调用，告知策略的下一步动作
+  pub on_retry: Option<OnRetry<F::Error>>,
+  /// Sticky cause once retries are exhausted, writes then return `Error::ReadOnly`
+  /// 重试耗尽后的粘滞原因，此后写入返回 `Error::ReadOnly`
+  pub bg_error: Option<Error<F::Error>>,
+  /// Share of a global write buffer, set to `WriteBuffer::register()`
+  /// 全局写缓冲中的份额，设置为 `WriteBuffer::register()`
+  pub buffer: Option<Member>,
+  /// Write-ahead log, None until `recover` is called
+  /// 预写日志，调用 `recover` 之前为 None
+  pub wal: Option<Wal>,
 
   /// Disk flush state manager
   /// 磁盘刷盘状态管理器
@@ -39,44 +95,262 @@ where
   /// 创建一个新的空 Mem，并指定轮转大小
   #[inline]
   pub fn new(rotate_size: usize, sst: F, discard: D) -> Self {
+    Self::with_table(rotate_size, sst, discard)
+  }
+}
+
+impl<F, D, T> Mem<F, D, T>
+where
+  F: MemToSst,
+  D: Discard,
+  T: Table,
+{
+  /// Create with a chosen table, e.g. `Mem::<_, _, Skip>::with_table(..)`
+  /// 使用指定的表创建，如 `Mem::<_, _, Skip>::with_table(..)`
+  #[inline]
+  pub fn with_table(rotate_size: usize, sst: F, discard: D) -> Self {
     Self {
-      now: Map::default(),
-      freeze: None,
+      now: Map::new(),
+      freeze: VecDeque::new(),
+      max_freeze: MAX_FREEZE,
       size: 0,
       rotate_size,
+      max_age: None,
+      born: None,
+      signal: Signal::default(),
+      retry: Retry::default(),
+      on_retry: None,
+      bg_error: None,
+      buffer: None,
+      wal: None,
       state: State::new(Disk::new(sst, discard)),
     }
   }
 
-  /// Rotate current map to frozen map and trigger flush
-  /// 将当前 Map 轮转为冻结 Map 并触发刷盘
+  /// Enable the WAL in `dir`: logs newer than `flushed` (`Levels::wal_id`)
+  /// are replayed into frozen maps and flushed, new writes go to a fresh log
+  /// 在 `dir` 中启用 WAL：新于 `flushed`（`Levels::wal_id`）的日志
+  /// 被回放为冻结 Map 并刷盘，新写入进入新日志
+  pub async fn recover(
+    &mut self,
+    dir: impl Into<PathBuf>,
+    flushed: u64,
+  ) -> Result<(), Error<F::Error>> {
+    let (wal, li) = Wal::open::<T>(dir, flushed).await.map_err(Error::Wal)?;
+    self.now.wal_id = wal.id;
+    for map in li {
+      self.push_freeze(map);
+    }
+    self.wal = Some(wal);
+    self.state.flush(&mut self.freeze)
+  }
+
+  /// Flush logged records and fsync, writes before this survive a crash
+  /// 刷出已记录的日志并 fsync，此前的写入在崩溃后仍然保留
+  #[inline]
+  pub async fn sync_wal(&self) -> Result<(), Error<F::Error>> {
+    match &self.wal {
+      Some(wal) => wal.sync().await.map_err(Error::Wal),
+      None => Ok(()),
+    }
+  }
+
+  /// Remove logs at or below `flushed`, call with `Levels::wal_id` once the manifest
+  /// write of `MemToSst::push` is durable, a log removed earlier could lose the map on crash
+  /// 删除不超过 `flushed` 的日志，在 `MemToSst::push` 的清单写入持久化后以 `Levels::wal_id` 调用，
+  /// 提前删除的日志可能在崩溃时丢失该 Map
+  pub async fn gc_wal(&mut self, flushed: u64) {
+    let live = self
+      .freeze
+      .front()
+      .map_or(self.now.wal_id, |m| m.wal_id)
+      .min(flushed + 1);
+    if let Some(wal) = &mut self.wal {
+      wal.gc(live).await;
+    }
+  }
+
+  /// Current write stall and its reason
+  /// 当前写入停顿及其原因
+  #[inline]
+  pub fn stall(&self) -> Stall {
+    self.signal.get()
+  }
+
+  /// Slow down or block a write as the stall signal asks
+  /// 按停顿信号的要求减速或阻塞写入
   #[cold]
-  pub fn rotate(&mut self) -> Result<(), crate::Error<F::Error>> {
-    let now = std::mem::take(&mut self.now);
-    let now = Rc::new(now);
+  pub(crate) async fn wait_stall(&self) {
+    match self.signal.get() {
+      Stall::None => {}
+      Stall::Delay(_) => compio::time::sleep(STALL_DELAY).await,
+      Stall::Stop(reason) => {
+        log::warn!("write stopped: {reason:?}");
+        self.signal.wait().await;
+      }
+    }
+  }
+
+  /// When the active map must rotate by age, None if it is empty or age is unlimited
+  /// 活跃 Map 因存活时间必须轮转的时刻，为空或不限时间时为 None
+  #[inline]
+  pub fn deadline(&self) -> Option<Instant> {
+    Some(self.born? + self.max_age?)
+  }
+
+  #[inline]
+  fn is_old(&self) -> bool {
+    self.deadline().is_some_and(|d| d <= Instant::now())
+  }
 
-    debug_assert!(
-      self.freeze.is_none(),
-      "freeze should be empty before rotate"
-    );
-    self.freeze = Some(now);
+  /// Resolves on a compio timer once the active map may be too old or the write buffer
+  /// forces it out, then call `maybe_rotate`.
+  /// The future borrows nothing, so writes go on while the owner's background loop waits
+  /// 使用 compio 定时器，在活跃 Map 可能过老或被写缓冲强制换出时完成，随后调用 `maybe_rotate`。
+  /// 该 future 不借用任何内容，所有者的后台循环等待期间写入照常进行
+  pub fn due(&self) -> impl Future<Output = ()> + 'static {
+    let (max_age, deadline) = (self.max_age, self.deadline());
+    let force = self.buffer.as_ref().map(Member::wait_force);
+    async move {
+      let age = async {
+        match (max_age, deadline) {
+          (Some(_), Some(deadline)) => compio::time::sleep_until(deadline).await,
+          (Some(max_age), None) => compio::time::sleep(max_age).await,
+          (None, _) => std::future::pending().await,
+        }
+      };
+      let force = async {
+        match force {
+          Some(force) => force.await,
+          None => std::future::pending().await,
+        }
+      };
+      futures::future::select(pin!(age), pin!(force)).await;
+    }
+  }
+
+  /// Rotate the active map if it is too old or forced by the write buffer,
+  /// so quiet tables still reach SSTs.
+  /// Returns false without rotating otherwise, e.g. the map was empty or written after `due` began
+  /// 活跃 Map 过老或被写缓冲强制时轮转它，使安静的表也能进入 SST。
+  /// 否则不轮转并返回 false，如 Map 为空或在 `due` 开始后才被写入
+  pub fn maybe_rotate(&mut self) -> Result<bool, Error<F::Error>> {
+    if self.forced() || self.is_old() {
+      self.rotate()?;
+      return Ok(true);
+    }
+    Ok(false)
+  }
+
+  /// Write buffer asked a non-empty active map to rotate
+  /// 写缓冲要求非空的活跃 Map 轮转
+  #[inline]
+  fn forced(&self) -> bool {
+    self.buffer.as_ref().is_some_and(Member::take_force) && self.born.is_some()
+  }
+
+  /// Sync `size` with the active map and report it to the write buffer
+  /// 将 `size` 与活跃 Map 同步，并上报给写缓冲
+  #[inline]
+  pub(crate) fn resize(&mut self) {
+    self.size = self.now.size();
+    if let Some(member) = &self.buffer {
+      member.set(self.size);
+    }
+  }
 
-    self.size = 0;
+  /// Rotate the active map if it holds anything, then wait until every frozen map is in SSTs
+  /// 若活跃 Map 有内容则轮转，然后等待所有冻结 Map 进入 SST
+  pub async fn flush(&mut self) -> Result<(), Error<F::Error>> {
+    if self.born.is_some() {
+      self.rotate()?;
+    }
+    self.wait_freeze(0).await
+  }
+
+  /// Before a write: honour stall, rotate if the active map is full or too old
+  /// 写入前：遵循停顿信号，活跃 Map 已满或过老时轮转
+  #[inline]
+  pub(crate) async fn reserve(&mut self) -> Result<(), Error<F::Error>> {
+    if self.bg_error.is_some() {
+      return Err(Error::ReadOnly);
+    }
+    if self.signal.get() != Stall::None {
+      self.wait_stall().await;
+    }
+    if self.size >= self.rotate_size || self.is_old() || self.forced() {
+      // 如果需要轮转且冻结队列已满，等待最老的刷盘完成，然后轮转，内联同步判断，可以减少await的开销
+      if self.freeze.len() >= self.max_freeze {
+        self.wait_freeze(self.max_freeze.saturating_sub(1)).await?;
+      }
+      self.rotate()?;
+    }
+    if self.born.is_none() {
+      self.born = Some(Instant::now());
+    }
+    Ok(())
+  }
+
+  /// Rotate current map into the freeze queue and trigger flush
+  /// 将当前 Map 轮转进冻结队列并触发刷盘
+  #[cold]
+  pub fn rotate(&mut self) -> Result<(), Error<F::Error>> {
+    let now = std::mem::take(&mut self.now);
+    self.push_freeze(now);
+    if let Some(wal) = &mut self.wal {
+      self.now.wal_id = wal.rotate();
+    }
+
+    self.born = None;
+    self.resize();
     self.state.flush(&mut self.freeze)
   }
 
-  /// Helper: Wait for freeze to be flushed with consistency check and retry
-  /// 辅助函数：等待 freeze 刷盘，带一致性检查和重试
+  /// Queue a map for flush, its bytes stay in the write buffer until it is flushed and dropped
+  /// 将 Map 加入刷盘队列，其字节留在写缓冲中直到刷盘并 drop
+  #[inline]
+  fn push_freeze(&mut self, mut map: Map<T>) {
+    if let Some(member) = &self.buffer {
+      map.held = Some(member.hold(map.size()));
+    }
+    self.freeze.push_back(Rc::new(map));
+  }
+
+  /// Helper: Wait until at most `keep` frozen maps remain,
+  /// failed flushes are retried per `retry`, then the memtable goes read-only
+  /// 辅助函数：等待直到最多剩余 `keep` 个冻结 Map，
+  /// 失败的刷盘按 `retry` 重试，之后内存表进入只读
   #[cold]
-  pub(crate) async fn wait_freeze(&mut self) -> Result<(), crate::Error<F::Error>> {
-    while self.freeze.is_some() {
-      if let Err(e) = self.state.wait(&mut self.freeze).await {
-        if let crate::Error::Sst(_) = e {
-          error!("flush freeze failed (retrying in 1s): {:?}", e);
-          compio::time::sleep(std::time::Duration::from_secs(1)).await;
-          continue;
+  pub(crate) async fn wait_freeze(&mut self, keep: usize) -> Result<(), Error<F::Error>> {
+    let mut attempt = 0;
+    while self.freeze.len() > keep {
+      if self.bg_error.is_some() {
+        return Err(Error::ReadOnly);
+      }
+      match self.state.wait(&mut self.freeze).await {
+        Ok(()) => attempt = 0,
+        Err(e @ Error::Sst(_)) => {
+          attempt += 1;
+          if self.retry.give_up(attempt) {
+            error!("flush freeze failed {attempt} times, read-only: {e:?}");
+            if let Some(f) = &self.on_retry {
+              f(RetryEvent::GiveUp { attempt, err: &e });
+            }
+            self.bg_error = Some(e);
+            return Err(Error::ReadOnly);
+          }
+          let delay = self.retry.delay(attempt);
+          error!("flush freeze failed (retry {attempt} in {delay:?}): {e:?}");
+          if let Some(f) = &self.on_retry {
+            f(RetryEvent::Retry {
+              attempt,
+              delay,
+              err: &e,
+            });
+          }
+          compio::time::sleep(delay).await;
         }
-        return Err(e);
+        Err(e) => return Err(e),
       }
     }
     Ok(())
@@ -1,5 +1,5 @@
-//! Merge iterator for two sorted streams
-//! 双路有序流的归并迭代器
+//! Merge iterators for sorted streams
+//! 有序流的归并迭代器
 
 use std::{cmp::Ordering, marker::PhantomData};
 
@@ -86,3 +86,59 @@ where
     }
   }
 }
+
+/// Merged iterator for N sorted streams, earlier source wins on equal keys
+/// N 路有序流的合并迭代器，键相同时靠前的源优先
+pub struct MergeIter<'a, I, O: Order>
+where
+  I: Iterator<Item = (&'a [u8], Pos)>,
+{
+  li: Vec<Source<'a, I>>,
+  _marker: PhantomData<O>,
+}
+
+impl<'a, I, O: Order> MergeIter<'a, I, O>
+where
+  I: Iterator<Item = (&'a [u8], Pos)>,
+{
+  /// Sources ordered newest first
+  /// 源按从新到旧排列
+  #[inline]
+  pub fn new(li: impl IntoIterator<Item = I>) -> Self {
+    Self {
+      li: li.into_iter().map(Source::new).collect(),
+      _marker: PhantomData,
+    }
+  }
+}
+
+impl<'a, I, O: Order> Iterator for MergeIter<'a, I, O>
+where
+  I: Iterator<Item = (&'a [u8], Pos)>,
+{
+  type Item = (&'a [u8], Pos);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    // Few sources (memtables), linear scan beats a heap
+    // 源很少（内存表），线性扫描优于堆
+    let mut min: Option<(usize, &'a [u8])> = None;
+    for (i, src) in self.li.iter().enumerate() {
+      if let Some((k, _)) = src.next
+        && min.is_none_or(|(_, m)| O::cmp(k, m) == Ordering::Less)
+      {
+        min = Some((i, k));
+      }
+    }
+    let (i, key) = min?;
+    // Skip older versions of the same key
+    // 跳过同一键的较老版本
+    for src in &mut self.li[i + 1..] {
+      if let Some((k, _)) = src.next
+        && k == key
+      {
+        src.pop();
+      }
+    }
+    self.li[i].pop()
+  }
+}
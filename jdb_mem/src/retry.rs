@@ -0,0 +1,62 @@
+//! Retry policy for failed memtable flushes
+//! 内存表刷盘失败的重试策略
+
+use std::time::Duration;
+
+/// Exponential backoff, the delay doubles per failed attempt up to `max_delay`
+/// 指数退避，每次失败延迟翻倍，直到 `max_delay`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+  /// Delay after the first failure
+  /// 首次失败后的延迟
+  pub base: Duration,
+  /// Upper bound of the delay
+  /// 延迟上限
+  pub max_delay: Duration,
+  /// Failures before giving up and going read-only, 0 retries forever
+  /// 放弃并进入只读前的失败次数，0 表示永远重试
+  pub max_attempts: u32,
+}
+
+impl Default for Retry {
+  fn default() -> Self {
+    Self {
+      base: Duration::from_millis(100),
+      max_delay: Duration::from_secs(10),
+      max_attempts: 10,
+    }
+  }
+}
+
+impl Retry {
+  /// Delay before retrying after failure number `attempt` (from 1)
+  /// 第 `attempt` 次失败（从 1 开始）后重试前的延迟
+  #[inline]
+  pub fn delay(&self, attempt: u32) -> Duration {
+    let shift = attempt.saturating_sub(1).min(31);
+    self.base.saturating_mul(1 << shift).min(self.max_delay)
+  }
+
+  /// Whether failure number `attempt` exhausts the policy
+  /// 第 `attempt` 次失败是否耗尽策略
+  #[inline]
+  pub fn give_up(&self, attempt: u32) -> bool {
+    self.max_attempts != 0 && attempt >= self.max_attempts
+  }
+}
+
+/// What the policy did with a failed flush, passed to `Mem::on_retry`
+/// 策略对失败刷盘的处理，传给 `Mem::on_retry`
+#[derive(Debug)]
+pub enum RetryEvent<'a, E> {
+  /// Will retry after `delay`
+  /// 将在 `delay` 后重试
+  Retry {
+    attempt: u32,
+    delay: Duration,
+    err: &'a E,
+  },
+  /// Gave up, the memtable is now read-only
+  /// 已放弃，内存表进入只读
+  GiveUp { attempt: u32, err: &'a E },
+}
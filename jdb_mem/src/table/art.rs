@@ -0,0 +1,559 @@
+//! Adaptive radix tree: inner nodes grow 4 -> 16 -> 48 -> 256 children,
+//! shared key bytes are compressed into node prefixes.
+//! Keys may be prefixes of each other, so an inner node can hold the entry
+//! ending at it.
+//! 自适应基数树：内部节点的子节点容量按 4 -> 16 -> 48 -> 256 增长，
+//! 共享的键字节压缩进节点前缀。
+//! 键可能互为前缀，因此内部节点可以持有恰好在此结束的条目。
+
+use std::ops::Bound;
+
+use jdb_base::{Flag, Pos};
+
+use super::{Table, after_start, before_end, owned, sealed::Sealed};
+use crate::{Key, arena::Arena};
+
+const NIL_POS: Pos = Pos {
+  ver: 0,
+  wal_id: 0,
+  offset_or_file_id: 0,
+  len: 0,
+  flag: Flag::INFILE,
+};
+
+/// Allocation free placeholder while a node is rebuilt
+/// 重建节点时使用的无分配占位符
+#[inline]
+fn nil() -> Node {
+  Node::Leaf(Leaf {
+    key: Key::empty(),
+    pos: NIL_POS,
+  })
+}
+
+#[derive(Debug)]
+struct Leaf {
+  key: Key,
+  pos: Pos,
+}
+
+#[derive(Debug)]
+enum Node {
+  Leaf(Leaf),
+  Inner(Box<Inner>),
+}
+
+#[derive(Debug)]
+struct Inner {
+  prefix: Box<[u8]>,
+  /// Entry whose key ends at this node
+  /// 键恰好在此节点结束的条目
+  val: Option<Leaf>,
+  kids: Kids,
+}
+
+impl Inner {
+  #[inline]
+  fn new(prefix: &[u8]) -> Self {
+    Self {
+      prefix: prefix.into(),
+      val: None,
+      kids: Kids::N4(Sorted::with_capacity(4)),
+    }
+  }
+
+  /// Heap bytes of the box holding this node and what it owns
+  /// 持有此节点的 box 及其所拥有内容的堆字节数
+  #[inline]
+  fn heap(&self) -> usize {
+    size_of::<Self>() + self.prefix.len() + self.kids.heap()
+  }
+
+  /// Place leaf under this node, `depth` is the key length consumed so far
+  /// 将叶子放到此节点下，`depth` 为已消耗的键长度
+  #[inline]
+  fn place(&mut self, leaf: Leaf, depth: usize) {
+    if leaf.key.len() == depth {
+      self.val = Some(leaf);
+    } else {
+      self.kids.add(leaf.key[depth], Node::Leaf(leaf));
+    }
+  }
+}
+
+/// Children sorted by byte
+/// 按字节排序的子节点
+#[derive(Debug)]
+struct Sorted {
+  keys: Vec<u8>,
+  child: Vec<Node>,
+}
+
+impl Sorted {
+  #[inline]
+  fn with_capacity(n: usize) -> Self {
+    Self {
+      keys: Vec::with_capacity(n),
+      child: Vec::with_capacity(n),
+    }
+  }
+}
+
+#[derive(Debug)]
+enum Kids {
+  N4(Sorted),
+  N16(Sorted),
+  /// index[byte] is slot + 1, 0 means empty
+  /// index[字节] 为槽位 + 1，0 表示空
+  N48(Box<[u8; 256]>, Vec<Node>),
+  N256(Box<[Option<Node>; 256]>),
+}
+
+impl Kids {
+  /// Heap bytes of the child arrays
+  /// 子节点数组的堆字节数
+  fn heap(&self) -> usize {
+    match self {
+      Self::N4(s) | Self::N16(s) => s.keys.capacity() + s.child.capacity() * size_of::<Node>(),
+      Self::N48(_, child) => 256 + child.capacity() * size_of::<Node>(),
+      Self::N256(_) => size_of::<[Option<Node>; 256]>(),
+    }
+  }
+
+  fn get(&self, b: u8) -> Option<&Node> {
+    match self {
+      Self::N4(s) | Self::N16(s) => s.keys.binary_search(&b).ok().map(|i| &s.child[i]),
+      Self::N48(index, child) => match index[b as usize] {
+        0 => None,
+        i => Some(&child[i as usize - 1]),
+      },
+      Self::N256(child) => child[b as usize].as_ref(),
+    }
+  }
+
+  fn get_mut(&mut self, b: u8) -> Option<&mut Node> {
+    match self {
+      Self::N4(s) | Self::N16(s) => s.keys.binary_search(&b).ok().map(|i| &mut s.child[i]),
+      Self::N48(index, child) => match index[b as usize] {
+        0 => None,
+        i => Some(&mut child[i as usize - 1]),
+      },
+      Self::N256(child) => child[b as usize].as_mut(),
+    }
+  }
+
+  /// Add child for an absent byte, growing the node when full
+  /// 为不存在的字节添加子节点，满时扩容
+  fn add(&mut self, b: u8, node: Node) {
+    match self {
+      Self::N4(s) if s.keys.len() == 4 => {
+        let mut s = std::mem::replace(s, Sorted::with_capacity(0));
+        s.keys.reserve_exact(12);
+        s.child.reserve_exact(12);
+        *self = Self::N16(s);
+      }
+      Self::N16(s) if s.keys.len() == 16 => {
+        let mut index = Box::new([0u8; 256]);
+        for (i, k) in s.keys.iter().enumerate() {
+          index[*k as usize] = i as u8 + 1;
+        }
+        let mut child = std::mem::take(&mut s.child);
+        child.reserve_exact(32);
+        *self = Self::N48(index, child);
+      }
+      Self::N48(index, child) if child.len() == 48 => {
+        let mut slot: Vec<Option<Node>> = std::mem::take(child).into_iter().map(Some).collect();
+        let index = std::mem::replace(index, Box::new([0; 256]));
+        *self = Self::N256(Box::new(std::array::from_fn(|k| match index[k] {
+          0 => None,
+          i => slot[i as usize - 1].take(),
+        })));
+      }
+      _ => {}
+    }
+    match self {
+      Self::N4(s) | Self::N16(s) => {
+        let i = s.keys.partition_point(|k| *k < b);
+        s.keys.insert(i, b);
+        s.child.insert(i, node);
+      }
+      Self::N48(index, child) => {
+        child.push(node);
+        index[b as usize] = child.len() as u8;
+      }
+      Self::N256(child) => child[b as usize] = Some(node),
+    }
+  }
+
+  /// First child with byte >= from (from in 0..=256)
+  /// 第一个字节 >= from 的子节点（from 取值 0..=256）
+  fn next(&self, from: usize) -> Option<(u8, &Node)> {
+    match self {
+      Self::N4(s) | Self::N16(s) => {
+        let i = s.keys.partition_point(|k| (*k as usize) < from);
+        s.keys.get(i).map(|k| (*k, &s.child[i]))
+      }
+      Self::N48(index, child) => (from..256)
+        .find(|k| index[*k] != 0)
+        .map(|k| (k as u8, &child[index[k] as usize - 1])),
+      Self::N256(child) => (from..256).find_map(|k| child[k].as_ref().map(|n| (k as u8, n))),
+    }
+  }
+
+  /// Last child with byte < to (to in 0..=256)
+  /// 最后一个字节 < to 的子节点（to 取值 0..=256）
+  fn prev(&self, to: usize) -> Option<(u8, &Node)> {
+    match self {
+      Self::N4(s) | Self::N16(s) => {
+        let i = s.keys.partition_point(|k| (*k as usize) < to);
+        i.checked_sub(1).map(|i| (s.keys[i], &s.child[i]))
+      }
+      Self::N48(index, child) => (0..to)
+        .rev()
+        .find(|k| index[*k] != 0)
+        .map(|k| (k as u8, &child[index[k] as usize - 1])),
+      Self::N256(child) => (0..to)
+        .rev()
+        .find_map(|k| child[k].as_ref().map(|n| (k as u8, n))),
+    }
+  }
+}
+
+/// Longest common prefix length
+/// 最长公共前缀长度
+#[inline]
+fn lcp(a: &[u8], b: &[u8]) -> usize {
+  a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+/// Insert below node, heap follows every node created or resized
+/// 在 node 下插入，heap 随每个新建或改变大小的节点更新
+fn insert(
+  node: &mut Node,
+  key: Key,
+  pos: Pos,
+  depth: usize,
+  heap: &mut usize,
+) -> Option<(Key, Pos)> {
+  match node {
+    Node::Leaf(leaf) => {
+      if leaf.key == key {
+        let old = std::mem::replace(&mut leaf.pos, pos);
+        return Some((key, old));
+      }
+      let common = lcp(&leaf.key[depth..], &key[depth..]);
+      let mut inner = Inner::new(&key[depth..depth + common]);
+      let Node::Leaf(old) = std::mem::replace(node, nil()) else {
+        unreachable!()
+      };
+      inner.place(old, depth + common);
+      inner.place(Leaf { key, pos }, depth + common);
+      *heap += inner.heap();
+      *node = Node::Inner(Box::new(inner));
+      None
+    }
+    Node::Inner(inner) => {
+      let p = lcp(&inner.prefix, &key[depth..]);
+      if p < inner.prefix.len() {
+        // Split prefix: new parent keeps the shared part
+        // 拆分前缀：新父节点保留共享部分
+        let mut parent = Inner::new(&inner.prefix[..p]);
+        let b = inner.prefix[p];
+        *heap -= inner.prefix.len();
+        inner.prefix = inner.prefix[p + 1..].into();
+        *heap += inner.prefix.len();
+        let old = std::mem::replace(node, nil());
+        parent.kids.add(b, old);
+        parent.place(Leaf { key, pos }, depth + p);
+        *heap += parent.heap();
+        *node = Node::Inner(Box::new(parent));
+        return None;
+      }
+      let depth = depth + p;
+      if key.len() == depth {
+        if let Some(val) = &mut inner.val {
+          let old = std::mem::replace(&mut val.pos, pos);
+          return Some((key, old));
+        }
+        inner.val = Some(Leaf { key, pos });
+        return None;
+      }
+      let b = key[depth];
+      if let Some(child) = inner.kids.get_mut(b) {
+        return insert(child, key, pos, depth + 1, heap);
+      }
+      *heap -= inner.kids.heap();
+      inner.kids.add(b, Node::Leaf(Leaf { key, pos }));
+      *heap += inner.kids.heap();
+      None
+    }
+  }
+}
+
+#[derive(Debug, Default)]
+pub struct Art {
+  root: Option<Node>,
+  len: usize,
+  /// Bytes of inner nodes, prefixes and child arrays
+  /// 内部节点、前缀和子节点数组的字节数
+  heap: usize,
+}
+
+impl Sealed for Art {}
+
+impl Table for Art {
+  type Iter<'a> = ArtIter<'a>;
+  type RevIter<'a> = ArtRevIter<'a>;
+
+  fn get(&self, key: &[u8]) -> Option<Pos> {
+    let mut node = self.root.as_ref()?;
+    let mut depth = 0;
+    loop {
+      match node {
+        Node::Leaf(leaf) => return (*leaf.key == *key).then_some(leaf.pos),
+        Node::Inner(inner) => {
+          if !key[depth..].starts_with(&inner.prefix) {
+            return None;
+          }
+          depth += inner.prefix.len();
+          if depth == key.len() {
+            return inner.val.as_ref().map(|leaf| leaf.pos);
+          }
+          node = inner.kids.get(key[depth])?;
+          depth += 1;
+        }
+      }
+    }
+  }
+
+  fn insert(&mut self, _: &mut Arena, key: Key, pos: Pos) -> Option<(Key, Pos)> {
+    let r = match &mut self.root {
+      Some(root) => insert(root, key, pos, 0, &mut self.heap),
+      None => {
+        self.root = Some(Node::Leaf(Leaf { key, pos }));
+        None
+      }
+    };
+    if r.is_none() {
+      self.len += 1;
+    }
+    r
+  }
+
+  #[inline]
+  fn len(&self) -> usize {
+    self.len
+  }
+
+  #[inline]
+  fn heap(&self) -> usize {
+    self.heap
+  }
+
+  fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> ArtIter<'_> {
+    let mut iter = ArtIter {
+      stack: Vec::new(),
+      start: owned(start),
+      end: owned(end),
+    };
+    if let Some(root) = &self.root {
+      match start {
+        Bound::Included(k) | Bound::Excluded(k) => iter.seek(root, k),
+        Bound::Unbounded => iter.expand(root),
+      }
+    }
+    iter
+  }
+
+  fn rev_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> ArtRevIter<'_> {
+    let mut iter = ArtRevIter {
+      stack: Vec::new(),
+      start: owned(start),
+      end: owned(end),
+    };
+    if let Some(root) = &self.root {
+      match end {
+        Bound::Included(k) | Bound::Excluded(k) => iter.seek(root, k),
+        Bound::Unbounded => iter.expand(root),
+      }
+    }
+    iter
+  }
+}
+
+/// Pending work of a depth-first walk
+/// 深度优先遍历的待处理项
+enum Frame<'a> {
+  Leaf(&'a Leaf),
+  /// Children not visited yet, cursor is a byte bound
+  /// 尚未访问的子节点，游标为字节边界
+  Kids(&'a Kids, usize),
+}
+
+pub struct ArtIter<'a> {
+  stack: Vec<Frame<'a>>,
+  start: Bound<Box<[u8]>>,
+  end: Bound<Box<[u8]>>,
+}
+
+impl<'a> ArtIter<'a> {
+  /// Queue node in ascending order: its own entry, then children
+  /// 按升序排队节点：先自身条目，再子节点
+  fn expand(&mut self, node: &'a Node) {
+    match node {
+      Node::Leaf(leaf) => self.stack.push(Frame::Leaf(leaf)),
+      Node::Inner(inner) => {
+        self.stack.push(Frame::Kids(&inner.kids, 0));
+        if let Some(val) = &inner.val {
+          self.stack.push(Frame::Leaf(val));
+        }
+      }
+    }
+  }
+
+  /// Queue only subtrees that may hold keys >= key
+  /// 仅排队可能包含 >= key 的子树
+  fn seek(&mut self, mut node: &'a Node, key: &[u8]) {
+    let mut depth = 0;
+    loop {
+      let Node::Inner(inner) = node else {
+        self.expand(node);
+        return;
+      };
+      let rest = &key[depth..];
+      let p = lcp(&inner.prefix, rest);
+      if p < inner.prefix.len() {
+        // Whole subtree is above key, or below and skipped
+        // 整个子树都大于 key，或都小于 key 而跳过
+        if p == rest.len() || inner.prefix[p] > rest[p] {
+          self.expand(node);
+        }
+        return;
+      }
+      depth += p;
+      if depth == key.len() {
+        self.expand(node);
+        return;
+      }
+      let b = key[depth];
+      self.stack.push(Frame::Kids(&inner.kids, b as usize + 1));
+      let Some(child) = inner.kids.get(b) else {
+        return;
+      };
+      node = child;
+      depth += 1;
+    }
+  }
+}
+
+impl<'a> Iterator for ArtIter<'a> {
+  type Item = (&'a [u8], Pos);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      match self.stack.pop()? {
+        Frame::Leaf(leaf) => {
+          if !after_start(&leaf.key, &self.start) {
+            continue;
+          }
+          if !before_end(&leaf.key, &self.end) {
+            self.stack.clear();
+            return None;
+          }
+          return Some((&leaf.key, leaf.pos));
+        }
+        Frame::Kids(kids, from) => {
+          if let Some((b, child)) = kids.next(from) {
+            self.stack.push(Frame::Kids(kids, b as usize + 1));
+            self.expand(child);
+          }
+        }
+      }
+    }
+  }
+}
+
+pub struct ArtRevIter<'a> {
+  stack: Vec<Frame<'a>>,
+  start: Bound<Box<[u8]>>,
+  end: Bound<Box<[u8]>>,
+}
+
+impl<'a> ArtRevIter<'a> {
+  /// Queue node in descending order: children, then its own entry
+  /// 按降序排队节点：先子节点，再自身条目
+  fn expand(&mut self, node: &'a Node) {
+    match node {
+      Node::Leaf(leaf) => self.stack.push(Frame::Leaf(leaf)),
+      Node::Inner(inner) => {
+        if let Some(val) = &inner.val {
+          self.stack.push(Frame::Leaf(val));
+        }
+        self.stack.push(Frame::Kids(&inner.kids, 256));
+      }
+    }
+  }
+
+  /// Queue only subtrees that may hold keys <= key
+  /// 仅排队可能包含 <= key 的子树
+  fn seek(&mut self, mut node: &'a Node, key: &[u8]) {
+    let mut depth = 0;
+    loop {
+      let Node::Inner(inner) = node else {
+        self.expand(node);
+        return;
+      };
+      let rest = &key[depth..];
+      let p = lcp(&inner.prefix, rest);
+      if p < inner.prefix.len() {
+        if p < rest.len() && inner.prefix[p] < rest[p] {
+          self.expand(node);
+        }
+        return;
+      }
+      depth += p;
+      // Own entry is a prefix of key, so never above it
+      // 自身条目是 key 的前缀，因此不会大于它
+      if let Some(val) = &inner.val {
+        self.stack.push(Frame::Leaf(val));
+      }
+      if depth == key.len() {
+        return;
+      }
+      let b = key[depth];
+      self.stack.push(Frame::Kids(&inner.kids, b as usize));
+      let Some(child) = inner.kids.get(b) else {
+        return;
+      };
+      node = child;
+      depth += 1;
+    }
+  }
+}
+
+impl<'a> Iterator for ArtRevIter<'a> {
+  type Item = (&'a [u8], Pos);
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      match self.stack.pop()? {
+        Frame::Leaf(leaf) => {
+          if !before_end(&leaf.key, &self.end) {
+            continue;
+          }
+          if !after_start(&leaf.key, &self.start) {
+            self.stack.clear();
+            return None;
+          }
+          return Some((&leaf.key, leaf.pos));
+        }
+        Frame::Kids(kids, to) => {
+          if let Some((b, child)) = kids.prev(to) {
+            self.stack.push(Frame::Kids(kids, b as usize));
+            self.expand(child);
+          }
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,468 @@
+//! B+tree with nodes in the Map's arena, leaves linked both ways for range scans
+//! 节点位于 Map 竞技场中的 B+ 树，叶子双向链接以便范围扫描
+
+use std::{
+  marker::PhantomData,
+  ops::Bound,
+  ptr::{self, NonNull},
+};
+
+use jdb_base::{Flag, Pos};
+
+use super::{Table, after_start, before_end, owned, sealed::Sealed};
+use crate::{Key, arena::Arena};
+
+/// Entries per leaf, separators per branch
+/// 每个叶子的条目数，每个分支的分隔键数
+const CAP: usize = 16;
+
+/// Nodes stay at least half full, so 16 levels are never reached
+/// 节点至少半满，因此永远不会达到 16 层
+const MAX_DEPTH: usize = 16;
+
+const NIL_POS: Pos = Pos {
+  ver: 0,
+  wal_id: 0,
+  offset_or_file_id: 0,
+  len: 0,
+  flag: Flag::INFILE,
+};
+
+/// Bytes of the first key of a right sibling, owned by that leaf
+/// 右兄弟第一个键的字节，由该叶子持有
+type Sep = *const [u8];
+
+const NIL_SEP: Sep = ptr::slice_from_raw_parts(NonNull::<u8>::dangling().as_ptr(), 0);
+
+/// Leaf or branch, known from the depth
+/// 叶子或分支，由深度决定
+type Child = *mut ();
+
+struct Leaf {
+  len: usize,
+  keys: [Key; CAP],
+  vals: [Pos; CAP],
+  prev: *mut Leaf,
+  next: *mut Leaf,
+}
+
+impl Leaf {
+  fn new() -> Self {
+    Self {
+      len: 0,
+      keys: [const { Key::empty() }; CAP],
+      vals: [NIL_POS; CAP],
+      prev: ptr::null_mut(),
+      next: ptr::null_mut(),
+    }
+  }
+
+  #[inline]
+  fn keys(&self) -> &[Key] {
+    &self.keys[..self.len]
+  }
+
+  /// Put the entry at i, len < CAP
+  /// 在 i 处放入条目，len < CAP
+  #[inline]
+  fn insert(&mut self, i: usize, key: Key, pos: Pos) {
+    // The placeholder at len rotates down to i
+    // len 处的占位符轮转到 i
+    self.keys[i..=self.len].rotate_right(1);
+    self.keys[i] = key;
+    self.vals.copy_within(i..self.len, i + 1);
+    self.vals[i] = pos;
+    self.len += 1;
+  }
+}
+
+struct Branch {
+  /// Separator count, kids has one more
+  /// 分隔键数，kids 比它多一个
+  len: usize,
+  /// kids[i + 1] holds keys >= seps[i]
+  /// kids[i + 1] 持有 >= seps[i] 的键
+  seps: [Sep; CAP],
+  kids: [Child; CAP + 1],
+}
+
+impl Branch {
+  /// Kid whose subtree may hold key
+  /// 子树可能包含 key 的子节点
+  #[inline]
+  fn kid(&self, key: &[u8]) -> usize {
+    // SAFETY: separators point into leaves of the same arena
+    // 安全：分隔键指向同一竞技场中的叶子
+    self.seps[..self.len].partition_point(|&s| unsafe { &*s } <= key)
+  }
+}
+
+#[derive(Debug)]
+pub struct BTree {
+  root: Child,
+  /// Branch levels above the leaves
+  /// 叶子之上的分支层数
+  depth: usize,
+  head: *mut Leaf,
+  tail: *mut Leaf,
+  len: usize,
+}
+
+impl Default for BTree {
+  fn default() -> Self {
+    Self {
+      root: ptr::null_mut(),
+      depth: 0,
+      head: ptr::null_mut(),
+      tail: ptr::null_mut(),
+      len: 0,
+    }
+  }
+}
+
+/// Entry slot, idx may equal the leaf length before normalizing
+/// 条目槽位，规整前 idx 可能等于叶子长度
+type Cursor = (*mut Leaf, usize);
+
+impl BTree {
+  /// SAFETY: x is a leaf of this table, it lives in the Map's arena which outlives &self
+  /// 安全：x 是本表的叶子，位于存活期长于 &self 的 Map 竞技场中
+  #[inline]
+  fn leaf(&self, x: *mut Leaf) -> &Leaf {
+    unsafe { &*x }
+  }
+
+  /// Leaf whose range holds key, the tree is not empty
+  /// 范围包含 key 的叶子，树非空
+  fn find(&self, key: &[u8]) -> *mut Leaf {
+    let mut node = self.root;
+    for _ in 0..self.depth {
+      // SAFETY: nodes above the leaf level are branches of this table
+      // 安全：叶子层之上的节点都是本表的分支
+      let b = unsafe { &*node.cast::<Branch>() };
+      node = b.kids[b.kid(key)];
+    }
+    node.cast()
+  }
+
+  /// First slot with key >= key, or > key when gt
+  /// 第一个键 >= key 的槽位，gt 时为 > key
+  fn seek(&self, key: &[u8], gt: bool) -> Cursor {
+    if self.root.is_null() {
+      return (ptr::null_mut(), 0);
+    }
+    let x = self.find(key);
+    let i = self
+      .leaf(x)
+      .keys()
+      .partition_point(|k| if gt { &**k <= key } else { &**k < key });
+    (x, i)
+  }
+
+  /// Step forward over the end of a leaf
+  /// 越过叶子末尾时前进到下一个叶子
+  #[inline]
+  fn fwd(&self, (x, i): Cursor) -> Cursor {
+    if !x.is_null() && i == self.leaf(x).len {
+      (self.leaf(x).next, 0)
+    } else {
+      (x, i)
+    }
+  }
+
+  /// Slot just before the given one
+  /// 给定槽位的前一个槽位
+  #[inline]
+  fn back(&self, (x, i): Cursor) -> Cursor {
+    if x.is_null() {
+      return self.last();
+    }
+    if i > 0 {
+      return (x, i - 1);
+    }
+    let prev = self.leaf(x).prev;
+    (
+      prev,
+      if prev.is_null() {
+        0
+      } else {
+        self.leaf(prev).len - 1
+      },
+    )
+  }
+
+  #[inline]
+  fn last(&self) -> Cursor {
+    let len = if self.tail.is_null() {
+      0
+    } else {
+      self.leaf(self.tail).len
+    };
+    (self.tail, len.saturating_sub(1))
+  }
+
+  /// First slot satisfying start
+  /// 第一个满足起始边界的槽位
+  fn lower(&self, start: Bound<&[u8]>) -> Cursor {
+    match start {
+      Bound::Included(k) => self.fwd(self.seek(k, false)),
+      Bound::Excluded(k) => self.fwd(self.seek(k, true)),
+      Bound::Unbounded => (self.head, 0),
+    }
+  }
+
+  /// Last slot satisfying end
+  /// 最后一个满足结束边界的槽位
+  fn upper(&self, end: Bound<&[u8]>) -> Cursor {
+    match end {
+      Bound::Included(k) => self.back(self.seek(k, true)),
+      Bound::Excluded(k) => self.back(self.seek(k, false)),
+      Bound::Unbounded => self.last(),
+    }
+  }
+
+  /// Split a full leaf around the new entry at i, return the right sibling
+  /// 围绕 i 处的新条目拆分满叶子，返回右兄弟
+  fn split_leaf(
+    &mut self,
+    arena: &mut Arena,
+    x: *mut Leaf,
+    i: usize,
+    key: Key,
+    pos: Pos,
+  ) -> (Sep, Child) {
+    // SAFETY: leaf of this table, &mut self excludes readers
+    // 安全：本表的叶子，&mut self 排除了读者
+    let left = unsafe { &mut *x };
+    // Appending keeps the left leaf full, sequential writes fill every leaf
+    // 追加时左叶子保持满，顺序写入可填满每个叶子
+    let mid = if i == CAP { CAP } else { CAP / 2 };
+    let mut right = Leaf::new();
+    for (j, k) in left.keys[mid..].iter_mut().enumerate() {
+      right.keys[j] = std::mem::replace(k, Key::empty());
+    }
+    right.vals[..CAP - mid].copy_from_slice(&left.vals[mid..]);
+    right.len = CAP - mid;
+    left.len = mid;
+    right.prev = x;
+    right.next = left.next;
+    let r = arena.put(right).as_ptr();
+    // SAFETY: r is fresh, left.next is a leaf of this table
+    // 安全：r 是新分配的，left.next 是本表的叶子
+    unsafe {
+      if i <= mid && i < CAP {
+        left.insert(i, key, pos);
+      } else {
+        (*r).insert(i - mid, key, pos);
+      }
+      match left.next.as_mut() {
+        Some(next) => next.prev = r,
+        None => self.tail = r,
+      }
+      left.next = r;
+      (ptr::from_ref::<[u8]>(&(*r).keys[0]), r.cast())
+    }
+  }
+}
+
+/// Split a full branch around the new separator at i, return the one moving up
+/// 围绕 i 处的新分隔键拆分满分支，返回上移的分隔键
+fn split_branch(
+  arena: &mut Arena,
+  left: &mut Branch,
+  i: usize,
+  sep: Sep,
+  kid: Child,
+) -> (Sep, Child) {
+  let mut seps = [NIL_SEP; CAP + 1];
+  let mut kids = [ptr::null_mut(); CAP + 2];
+  seps[..i].copy_from_slice(&left.seps[..i]);
+  seps[i] = sep;
+  seps[i + 1..].copy_from_slice(&left.seps[i..]);
+  kids[..=i].copy_from_slice(&left.kids[..=i]);
+  kids[i + 1] = kid;
+  kids[i + 2..].copy_from_slice(&left.kids[i + 1..]);
+
+  let mid = if i == CAP { CAP } else { CAP / 2 };
+  let mut right = Branch {
+    len: CAP - mid,
+    seps: [NIL_SEP; CAP],
+    kids: [ptr::null_mut(); CAP + 1],
+  };
+  right.seps[..CAP - mid].copy_from_slice(&seps[mid + 1..]);
+  right.kids[..=CAP - mid].copy_from_slice(&kids[mid + 1..]);
+  left.len = mid;
+  left.seps[..mid].copy_from_slice(&seps[..mid]);
+  left.kids[..=mid].copy_from_slice(&kids[..=mid]);
+  (seps[mid], arena.put(right).as_ptr().cast())
+}
+
+impl Sealed for BTree {}
+
+impl Table for BTree {
+  type Iter<'a> = BTreeIter<'a>;
+  type RevIter<'a> = BTreeRevIter<'a>;
+
+  #[inline]
+  fn get(&self, key: &[u8]) -> Option<Pos> {
+    let (x, i) = self.seek(key, false);
+    if x.is_null() {
+      return None;
+    }
+    let leaf = self.leaf(x);
+    (i < leaf.len && *leaf.keys[i] == *key).then(|| leaf.vals[i])
+  }
+
+  fn insert(&mut self, arena: &mut Arena, key: Key, pos: Pos) -> Option<(Key, Pos)> {
+    if self.root.is_null() {
+      let x = arena.put(Leaf::new()).as_ptr();
+      (self.root, self.head, self.tail) = (x.cast(), x, x);
+    }
+    let mut path = [(ptr::null_mut::<Branch>(), 0); MAX_DEPTH];
+    let mut node = self.root;
+    for step in &mut path[..self.depth] {
+      let b = node.cast::<Branch>();
+      // SAFETY: nodes above the leaf level are branches of this table
+      // 安全：叶子层之上的节点都是本表的分支
+      let i = unsafe { (*b).kid(&key) };
+      *step = (b, i);
+      node = unsafe { (*b).kids[i] };
+    }
+    let x = node.cast::<Leaf>();
+    // SAFETY: leaf of this table, &mut self excludes readers
+    // 安全：本表的叶子，&mut self 排除了读者
+    let leaf = unsafe { &mut *x };
+    let i = match leaf.keys().binary_search_by(|k| (**k).cmp(&key)) {
+      Ok(i) => {
+        let old = std::mem::replace(&mut leaf.vals[i], pos);
+        return Some((key, old));
+      }
+      Err(i) => i,
+    };
+    self.len += 1;
+    if leaf.len < CAP {
+      leaf.insert(i, key, pos);
+      return None;
+    }
+
+    let (mut sep, mut kid) = self.split_leaf(arena, x, i, key, pos);
+    for &(b, i) in path[..self.depth].iter().rev() {
+      // SAFETY: branch on the path, &mut self excludes readers
+      // 安全：路径上的分支，&mut self 排除了读者
+      let b = unsafe { &mut *b };
+      if b.len < CAP {
+        b.seps.copy_within(i..b.len, i + 1);
+        b.seps[i] = sep;
+        b.kids.copy_within(i + 1..=b.len, i + 2);
+        b.kids[i + 1] = kid;
+        b.len += 1;
+        return None;
+      }
+      (sep, kid) = split_branch(arena, b, i, sep, kid);
+    }
+
+    let mut root = Branch {
+      len: 1,
+      seps: [NIL_SEP; CAP],
+      kids: [ptr::null_mut(); CAP + 1],
+    };
+    root.seps[0] = sep;
+    root.kids[0] = self.root;
+    root.kids[1] = kid;
+    self.root = arena.put(root).as_ptr().cast();
+    self.depth += 1;
+    None
+  }
+
+  #[inline]
+  fn len(&self) -> usize {
+    self.len
+  }
+
+  #[inline]
+  fn heap(&self) -> usize {
+    0
+  }
+
+  fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> BTreeIter<'_> {
+    let (leaf, idx) = self.lower(start);
+    BTreeIter {
+      leaf,
+      idx,
+      end: owned(end),
+      _tree: PhantomData,
+    }
+  }
+
+  fn rev_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> BTreeRevIter<'_> {
+    let (leaf, idx) = self.upper(end);
+    BTreeRevIter {
+      leaf,
+      idx,
+      start: owned(start),
+      _tree: PhantomData,
+    }
+  }
+}
+
+pub struct BTreeIter<'a> {
+  leaf: *mut Leaf,
+  idx: usize,
+  end: Bound<Box<[u8]>>,
+  _tree: PhantomData<&'a BTree>,
+}
+
+impl<'a> Iterator for BTreeIter<'a> {
+  type Item = (&'a [u8], Pos);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    // SAFETY: leaves of the borrowed tree, alive for 'a
+    // 安全：被借用树的叶子，在 'a 期间存活
+    let leaf: &'a Leaf = unsafe { self.leaf.as_ref()? };
+    let key = &*leaf.keys[self.idx];
+    if !before_end(key, &self.end) {
+      self.leaf = ptr::null_mut();
+      return None;
+    }
+    let pos = leaf.vals[self.idx];
+    self.idx += 1;
+    if self.idx == leaf.len {
+      (self.leaf, self.idx) = (leaf.next, 0);
+    }
+    Some((key, pos))
+  }
+}
+
+pub struct BTreeRevIter<'a> {
+  leaf: *mut Leaf,
+  idx: usize,
+  start: Bound<Box<[u8]>>,
+  _tree: PhantomData<&'a BTree>,
+}
+
+impl<'a> Iterator for BTreeRevIter<'a> {
+  type Item = (&'a [u8], Pos);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    // SAFETY: leaves of the borrowed tree, alive for 'a
+    // 安全：被借用树的叶子，在 'a 期间存活
+    let leaf: &'a Leaf = unsafe { self.leaf.as_ref()? };
+    let key = &*leaf.keys[self.idx];
+    if !after_start(key, &self.start) {
+      self.leaf = ptr::null_mut();
+      return None;
+    }
+    let pos = leaf.vals[self.idx];
+    if self.idx == 0 {
+      self.leaf = leaf.prev;
+      // SAFETY: as above
+      // 安全：同上
+      self.idx = unsafe { self.leaf.as_ref() }.map_or(0, |l| l.len - 1);
+    } else {
+      self.idx -= 1;
+    }
+    Some((key, pos))
+  }
+}
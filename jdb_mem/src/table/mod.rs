@@ -0,0 +1,97 @@
+//! Pluggable ordered storage behind `Map`
+//! `Map` 背后可插拔的有序存储
+
+mod art;
+mod btree;
+mod skip;
+
+use std::ops::Bound;
+
+pub use art::{Art, ArtIter, ArtRevIter};
+pub use btree::{BTree, BTreeIter, BTreeRevIter};
+use jdb_base::Pos;
+pub use skip::{Skip, SkipIter, SkipRevIter};
+
+use crate::{Key, arena::Arena};
+
+mod sealed {
+  /// Tables keep keys and nodes in the Map's arena, so only this crate implements them
+  /// 表将键和节点保存在 Map 的竞技场中，因此只有本 crate 能实现
+  pub trait Sealed {}
+}
+
+/// Ordered key -> Pos storage of a memtable.
+/// Entries are never removed, the whole table is dropped after flush.
+/// Sealed: implemented by BTree, Skip and Art only
+/// 内存表的有序 键 -> Pos 存储。
+/// 条目不会被删除，整个表在刷盘后被释放。
+/// 密封：仅由 BTree、Skip 和 Art 实现
+pub trait Table: sealed::Sealed + Default + 'static {
+  /// Ascending iterator
+  /// 升序迭代器
+  type Iter<'a>: Iterator<Item = (&'a [u8], Pos)>
+  where
+    Self: 'a;
+
+  /// Descending iterator
+  /// 降序迭代器
+  type RevIter<'a>: Iterator<Item = (&'a [u8], Pos)>
+  where
+    Self: 'a;
+
+  fn get(&self, key: &[u8]) -> Option<Pos>;
+
+  /// Insert or overwrite, on overwrite return the given key with the old Pos.
+  /// Keys point into the Map's arena, so they must stay in the table.
+  /// New nodes may be placed in the same arena
+  /// 插入或覆盖，覆盖时返回传入的键和旧 Pos。
+  /// 键指向 Map 的竞技场，因此必须留在表内。
+  /// 新节点可以放在同一个竞技场中
+  fn insert(&mut self, arena: &mut Arena, key: Key, pos: Pos) -> Option<(Key, Pos)>;
+
+  fn len(&self) -> usize;
+
+  /// Bytes allocated outside the Map's arena
+  /// 在 Map 竞技场之外分配的字节数
+  fn heap(&self) -> usize;
+
+  #[inline]
+  fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::Iter<'_>;
+
+  /// Descending over (start, end), same bounds as `range`
+  /// 在 (start, end) 上降序迭代，边界与 `range` 相同
+  fn rev_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self::RevIter<'_>;
+}
+
+/// Owned copy of a bound, for iterators outliving the caller's range
+/// 边界的自有副本，供生命周期长于调用方范围的迭代器使用
+#[inline]
+pub(crate) fn owned(b: Bound<&[u8]>) -> Bound<Box<[u8]>> {
+  b.map(Box::from)
+}
+
+/// Check key is not past the end bound
+/// 检查键未超过结束边界
+#[inline]
+pub(crate) fn before_end(key: &[u8], end: &Bound<Box<[u8]>>) -> bool {
+  match end {
+    Bound::Included(e) => key <= e.as_ref(),
+    Bound::Excluded(e) => key < e.as_ref(),
+    Bound::Unbounded => true,
+  }
+}
+
+/// Check key is not before the start bound
+/// 检查键未早于起始边界
+#[inline]
+pub(crate) fn after_start(key: &[u8], start: &Bound<Box<[u8]>>) -> bool {
+  match start {
+    Bound::Included(s) => key >= s.as_ref(),
+    Bound::Excluded(s) => key > s.as_ref(),
+    Bound::Unbounded => true,
+  }
+}
@@ -0,0 +1,319 @@
+//! Skiplist with nodes in the Map's arena, each followed by its forward links
+//! 节点位于 Map 竞技场中的跳表，每个节点后紧跟其前向链接
+
+use std::{alloc::Layout, ops::Bound, ptr};
+
+use jdb_base::Pos;
+
+use super::{Table, after_start, before_end, owned, sealed::Sealed};
+use crate::{Key, arena::Arena};
+
+const MAX_HEIGHT: usize = 12;
+
+/// Null is the head as a predecessor and none as a successor
+/// 空指针作为前驱时表示头节点，作为后继时表示无
+type Link = *mut Node;
+
+/// Header of a node, `height` links follow it in the arena
+/// 节点头，竞技场中其后紧跟 `height` 个链接
+#[derive(Debug)]
+struct Node {
+  key: Key,
+  pos: Pos,
+  /// Previous node at level 0, for reverse iteration
+  /// 第 0 层的前驱节点，用于反向迭代
+  prev: Link,
+}
+
+/// Offset of the links behind every node header
+/// 每个节点头之后链接的偏移
+const LINKS: usize = size_of::<Node>().next_multiple_of(align_of::<Link>());
+
+impl Node {
+  /// Node with its links, laid out back to back
+  /// 节点及其链接，首尾相接布局
+  #[inline]
+  fn layout(height: usize) -> Layout {
+    // Can't fail: height <= MAX_HEIGHT and Node holds a Link, so it is aligned for one
+    // 不会失败：height <= MAX_HEIGHT，且 Node 含有 Link，因此满足其对齐
+    Layout::from_size_align(LINKS + height * size_of::<Link>(), align_of::<Node>()).unwrap()
+  }
+}
+
+#[derive(Debug)]
+pub struct Skip {
+  head: [Link; MAX_HEIGHT],
+  height: usize,
+  tail: Link,
+  len: usize,
+  seed: u64,
+}
+
+impl Default for Skip {
+  fn default() -> Self {
+    Self {
+      head: [ptr::null_mut(); MAX_HEIGHT],
+      height: 1,
+      tail: ptr::null_mut(),
+      len: 0,
+      seed: 0x9E37_79B9_7F4A_7C15,
+    }
+  }
+}
+
+impl Skip {
+  /// SAFETY: x is a node of this table, it lives in the Map's arena which outlives &self
+  /// 安全：x 是本表的节点，位于存活期长于 &self 的 Map 竞技场中
+  #[inline]
+  fn node(&self, x: Link) -> &Node {
+    unsafe { &*x }
+  }
+
+  #[inline]
+  fn key(&self, x: Link) -> &[u8] {
+    &self.node(x).key
+  }
+
+  /// Link slot of x at level, x is never the head
+  /// x 在某层的链接槽，x 不是头节点
+  #[inline]
+  fn slot(x: Link, level: usize) -> *mut Link {
+    // SAFETY: x was allocated with at least level + 1 links
+    // 安全：x 分配时至少带有 level + 1 个链接
+    unsafe { x.byte_add(LINKS).cast::<Link>().add(level) }
+  }
+
+  #[inline]
+  fn next(&self, x: Link, level: usize) -> Link {
+    if x.is_null() {
+      self.head[level]
+    } else {
+      // SAFETY: links below the node height are always written
+      // 安全：节点高度以下的链接总是已写入
+      unsafe { *Self::slot(x, level) }
+    }
+  }
+
+  #[inline]
+  fn set_next(&mut self, x: Link, level: usize, to: Link) {
+    if x.is_null() {
+      self.head[level] = to;
+    } else {
+      // SAFETY: same as next, &mut self excludes readers
+      // 安全：同 next，&mut self 排除了读者
+      unsafe { *Self::slot(x, level) = to }
+    }
+  }
+
+  /// Previous node, the tail for null
+  /// 前驱节点，空指针的前驱为尾节点
+  #[inline]
+  fn prev(&self, x: Link) -> Link {
+    if x.is_null() {
+      self.tail
+    } else {
+      self.node(x).prev
+    }
+  }
+
+  /// Find first node >= key, filling predecessors per level
+  /// 查找第一个 >= key 的节点，并填充每层的前驱
+  fn find(&self, key: &[u8], preds: &mut [Link; MAX_HEIGHT]) -> Link {
+    let mut x = ptr::null_mut();
+    for level in (0..self.height).rev() {
+      loop {
+        let next = self.next(x, level);
+        if next.is_null() || self.key(next) >= key {
+          break;
+        }
+        x = next;
+      }
+      preds[level] = x;
+    }
+    self.next(x, 0)
+  }
+
+  #[inline]
+  fn seek_ge(&self, key: &[u8]) -> Link {
+    self.find(key, &mut [ptr::null_mut(); MAX_HEIGHT])
+  }
+
+  #[inline]
+  fn seek_gt(&self, key: &[u8]) -> Link {
+    let x = self.seek_ge(key);
+    if !x.is_null() && self.key(x) == key {
+      self.next(x, 0)
+    } else {
+      x
+    }
+  }
+
+  /// First node satisfying start
+  /// 第一个满足起始边界的节点
+  fn lower(&self, start: Bound<&[u8]>) -> Link {
+    match start {
+      Bound::Included(k) => self.seek_ge(k),
+      Bound::Excluded(k) => self.seek_gt(k),
+      Bound::Unbounded => self.head[0],
+    }
+  }
+
+  /// Last node satisfying end
+  /// 最后一个满足结束边界的节点
+  fn upper(&self, end: Bound<&[u8]>) -> Link {
+    match end {
+      Bound::Included(k) => self.prev(self.seek_gt(k)),
+      Bound::Excluded(k) => self.prev(self.seek_ge(k)),
+      Bound::Unbounded => self.tail,
+    }
+  }
+
+  /// Height with p = 1/4 per level (xorshift)
+  /// 每层概率 1/4 的高度（xorshift）
+  fn random_height(&mut self) -> usize {
+    let mut x = self.seed;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    self.seed = x;
+    let mut height = 1;
+    while height < MAX_HEIGHT && x & 3 == 0 {
+      height += 1;
+      x >>= 2;
+    }
+    height
+  }
+}
+
+impl Sealed for Skip {}
+
+impl Table for Skip {
+  type Iter<'a> = SkipIter<'a>;
+  type RevIter<'a> = SkipRevIter<'a>;
+
+  #[inline]
+  fn get(&self, key: &[u8]) -> Option<Pos> {
+    let x = self.seek_ge(key);
+    (!x.is_null() && self.key(x) == key).then(|| self.node(x).pos)
+  }
+
+  fn insert(&mut self, arena: &mut Arena, key: Key, pos: Pos) -> Option<(Key, Pos)> {
+    let mut preds = [ptr::null_mut(); MAX_HEIGHT];
+    let found = self.find(&key, &mut preds);
+    if !found.is_null() && self.key(found) == &*key {
+      // SAFETY: node of this table, &mut self excludes readers
+      // 安全：本表的节点，&mut self 排除了读者
+      let old = std::mem::replace(unsafe { &mut (*found).pos }, pos);
+      return Some((key, old));
+    }
+
+    let height = self.random_height();
+    if height > self.height {
+      // preds above the old height are already the head
+      // 旧高度以上的前驱已是头节点
+      self.height = height;
+    }
+    let x = arena
+      .alloc_layout(Node::layout(height))
+      .cast::<Node>()
+      .as_ptr();
+    // SAFETY: fresh arena memory sized for the header and height links
+    // 安全：为节点头和 height 个链接分配的新竞技场内存
+    unsafe {
+      x.write(Node {
+        key,
+        pos,
+        prev: preds[0],
+      })
+    };
+    for (level, pred) in preds.iter().enumerate().take(height) {
+      // SAFETY: level < height
+      // 安全：level < height
+      unsafe { Self::slot(x, level).write(self.next(*pred, level)) };
+      self.set_next(*pred, level, x);
+    }
+    if found.is_null() {
+      self.tail = x;
+    } else {
+      // SAFETY: node of this table
+      // 安全：本表的节点
+      unsafe { (*found).prev = x };
+    }
+    self.len += 1;
+    None
+  }
+
+  #[inline]
+  fn len(&self) -> usize {
+    self.len
+  }
+
+  #[inline]
+  fn heap(&self) -> usize {
+    0
+  }
+
+  fn range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> SkipIter<'_> {
+    SkipIter {
+      skip: self,
+      cur: self.lower(start),
+      end: owned(end),
+    }
+  }
+
+  fn rev_range(&self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> SkipRevIter<'_> {
+    SkipRevIter {
+      skip: self,
+      cur: self.upper(end),
+      start: owned(start),
+    }
+  }
+}
+
+pub struct SkipIter<'a> {
+  skip: &'a Skip,
+  cur: Link,
+  end: Bound<Box<[u8]>>,
+}
+
+impl<'a> Iterator for SkipIter<'a> {
+  type Item = (&'a [u8], Pos);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.cur.is_null() {
+      return None;
+    }
+    let node = self.skip.node(self.cur);
+    if !before_end(&node.key, &self.end) {
+      self.cur = ptr::null_mut();
+      return None;
+    }
+    self.cur = self.skip.next(self.cur, 0);
+    Some((&node.key, node.pos))
+  }
+}
+
+pub struct SkipRevIter<'a> {
+  skip: &'a Skip,
+  cur: Link,
+  start: Bound<Box<[u8]>>,
+}
+
+impl<'a> Iterator for SkipRevIter<'a> {
+  type Item = (&'a [u8], Pos);
+
+  #[inline]
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.cur.is_null() {
+      return None;
+    }
+    let node = self.skip.node(self.cur);
+    if !after_start(&node.key, &self.start) {
+      self.cur = ptr::null_mut();
+      return None;
+    }
+    self.cur = node.prev;
+    Some((&node.key, node.pos))
+  }
+}
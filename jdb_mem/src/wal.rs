@@ -0,0 +1,263 @@
+//! Write-ahead log: one file per memtable, replayed after a crash
+//! 预写日志：每个内存表一个文件，崩溃后回放
+
+use std::path::{Path, PathBuf};
+
+use jdb_base::Pos;
+use jdb_fs::{
+  BufFile, DataLen, Error, Item, Result,
+  item::{ParseResult, parse, write},
+  read_write,
+};
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+use crate::{Map, WriteBatch, table::Table};
+
+/// Log file extension
+/// 日志文件扩展名
+pub const EXT: &str = "wal";
+
+/// Record holds an encoded `WriteBatch`
+/// 记录内容为编码后的 `WriteBatch`
+const BATCH: u8 = 0;
+/// Record holds an encoded range tombstone
+/// 记录内容为编码后的范围墓碑
+const RANGE_DEL: u8 = 1;
+
+/// Record head, crc32 covers the data since `Row` only checks the head
+/// 记录头，crc32 校验数据，因为 `Row` 只校验头部
+#[derive(Debug, Clone, Copy, FromBytes, IntoBytes, Immutable, KnownLayout, Unaligned)]
+#[repr(C, packed)]
+pub struct Head {
+  pub kind: u8,
+  pub len: u32,
+  pub crc32: u32,
+}
+
+impl DataLen for Head {
+  #[inline]
+  fn data_len(&self) -> usize {
+    self.len as usize
+  }
+}
+
+struct Rec;
+
+impl Item for Rec {
+  const MAGIC: u8 = 0x57;
+  type Head = Head;
+}
+
+type RangeDelRec = (Box<[u8]>, Box<[u8]>, Pos);
+
+/// Path of log `id` in `dir`
+/// `dir` 中日志 `id` 的路径
+#[inline]
+pub fn path(dir: &Path, id: u64) -> PathBuf {
+  dir.join(format!("{id}.{EXT}"))
+}
+
+/// Appends records to the log of the active map
+/// 向活跃 Map 的日志追加记录
+pub struct Wal {
+  dir: PathBuf,
+  /// Log id of the active map
+  /// 活跃 Map 的日志 id
+  pub id: u64,
+  /// Oldest log id still on disk
+  /// 磁盘上仍存在的最老日志 id
+  oldest: u64,
+  /// None until the first append after a rotate
+  /// 轮转后首次追加前为 None
+  fs: Option<BufFile>,
+  /// Previous log, synced before the next one opens
+  /// 上一个日志，在下一个日志打开前同步
+  prev: Option<BufFile>,
+}
+
+impl Wal {
+  /// Replay logs newer than `flushed` into maps, oldest first,
+  /// logs at or below `flushed` are already in SSTs and get removed, so do logs that replay to nothing
+  /// 将新于 `flushed` 的日志回放为 Map（最老的在前），
+  /// 不超过 `flushed` 的日志已在 SST 中，将被删除，回放为空的日志也一样
+  pub async fn open<T: Table>(
+    dir: impl Into<PathBuf>,
+    flushed: u64,
+  ) -> Result<(Self, Vec<Map<T>>)> {
+    let dir = dir.into();
+    compio::fs::create_dir_all(&dir).await?;
+
+    let ids = {
+      let dir = dir.clone();
+      compio::runtime::spawn_blocking(move || list(&dir))
+        .await
+        .unwrap_or_else(|e| std::panic::resume_unwind(e))?
+    };
+
+    let mut li = Vec::with_capacity(ids.len());
+    for &id in &ids {
+      let file = path(&dir, id);
+      if id > flushed {
+        let mut map = replay::<T>(&file).await?;
+        // Empty or torn from its first record, nothing to flush
+        // 为空或从第一条记录起就残缺，无需刷盘
+        if !map.is_empty() {
+          map.wal_id = id;
+          li.push(map);
+          continue;
+        }
+      }
+      compio::fs::remove_file(&file).await?;
+    }
+
+    let id = ids.last().map_or(flushed, |&id| id.max(flushed)) + 1;
+    Ok((
+      Self {
+        dir,
+        id,
+        oldest: li.first().map_or(id, |m| m.wal_id),
+        fs: None,
+        prev: None,
+      },
+      li,
+    ))
+  }
+
+  /// Start a new log for the next active map, opened lazily on append
+  /// 为下一个活跃 Map 开始新日志，追加时延迟打开
+  #[inline]
+  pub fn rotate(&mut self) -> u64 {
+    if let Some(fs) = self.fs.take() {
+      self.prev = Some(fs);
+    }
+    self.id += 1;
+    self.id
+  }
+
+  /// Remove logs older than `live`, whose maps are in SSTs
+  /// 删除早于 `live` 的日志，它们的 Map 已在 SST 中
+  pub async fn gc(&mut self, live: u64) {
+    while self.oldest < live {
+      let file = path(&self.dir, self.oldest);
+      if let Err(e) = compio::fs::remove_file(&file).await
+        && e.kind() != std::io::ErrorKind::NotFound
+      {
+        log::warn!("rm {}: {e}", file.display());
+        return;
+      }
+      self.oldest += 1;
+    }
+  }
+
+  /// Log a single put
+  /// 记录单个写入
+  #[inline]
+  pub async fn put(&mut self, key: &[u8], pos: Pos) -> Result<()> {
+    let mut batch = WriteBatch::new();
+    batch.put(key, pos);
+    self.write(&batch).await
+  }
+
+  /// Log a batch as one record, replayed all or nothing
+  /// 将批次记录为一条记录，回放时全有或全无
+  #[inline]
+  pub async fn write(&mut self, batch: &WriteBatch) -> Result<()> {
+    self.append(BATCH, batch.encode()).await
+  }
+
+  /// Log a range tombstone
+  /// 记录范围墓碑
+  #[inline]
+  pub async fn delete_range(&mut self, start: &[u8], end: &[u8], pos: Pos) -> Result<()> {
+    let rec: RangeDelRec = (start.into(), end.into(), pos);
+    self.append(RANGE_DEL, bitcode::encode(&rec)).await
+  }
+
+  /// Flush buffered records and fsync
+  /// 刷出缓冲的记录并 fsync
+  pub async fn sync(&self) -> Result<()> {
+    for fs in [&self.prev, &self.fs].into_iter().flatten() {
+      fs.sync().await?;
+    }
+    Ok(())
+  }
+
+  async fn append(&mut self, kind: u8, data: Vec<u8>) -> Result<()> {
+    let head = Head {
+      kind,
+      len: data.len() as u32,
+      crc32: crc32fast::hash(&data),
+    };
+    if self.fs.is_none() {
+      if let Some(prev) = self.prev.take() {
+        prev.sync().await?;
+      }
+      let fs = read_write(path(&self.dir, self.id)).await?;
+      self.fs = Some(BufFile::new(fs, 0));
+    }
+    // Safe: set above
+    // 安全：上面已设置
+    let fs = unsafe { self.fs.as_mut().unwrap_unchecked() };
+    write::<Rec>(head, data, fs).await?;
+    Ok(())
+  }
+}
+
+/// Log ids in `dir`, oldest first, blocking so run it off the runtime thread
+/// `dir` 中的日志 id，最老的在前，会阻塞，需在运行时线程之外执行
+fn list(dir: &Path) -> std::io::Result<Vec<u64>> {
+  let mut ids = Vec::new();
+  for entry in std::fs::read_dir(dir)? {
+    let file = entry?.path();
+    if file.extension().is_some_and(|e| e == EXT)
+      && let Some(id) = file
+        .file_stem()
+        .and_then(|s| s.to_str()?.parse::<u64>().ok())
+    {
+      ids.push(id);
+    }
+  }
+  ids.sort_unstable();
+  Ok(ids)
+}
+
+/// Rebuild one map from its log, stopping at the first torn or corrupt record
+/// 从日志重建一个 Map，遇到第一条残缺或损坏的记录时停止
+async fn replay<T: Table>(file: &Path) -> Result<Map<T>> {
+  let bin = compio::fs::read(file).await?;
+  let mut map = Map::new();
+  let mut at = 0;
+  while at < bin.len() {
+    let head = match parse::<Rec>(&bin[at..]) {
+      ParseResult::Ok(head) => head,
+      ParseResult::NeedMore => break,
+      ParseResult::Err(e, _) => {
+        log::warn!("{} at {at}: {e}, stop replay", file.display());
+        break;
+      }
+    };
+    let start = at + Rec::ROW_SIZE;
+    let Some(data) = bin.get(start..start + head.data_len()) else {
+      break;
+    };
+    if crc32fast::hash(data) != head.crc32 {
+      log::warn!("{} at {at}: data crc, stop replay", file.display());
+      break;
+    }
+    match head.kind {
+      BATCH => {
+        let batch = WriteBatch::decode(data).map_err(|_| Error::Decode)?;
+        for (key, pos) in batch.iter() {
+          map.put(key, pos);
+        }
+      }
+      RANGE_DEL => {
+        let (start, end, pos): RangeDelRec = bitcode::decode(data).map_err(|_| Error::Decode)?;
+        map.delete_range(&start, &end, pos);
+      }
+      _ => return Err(Error::Decode),
+    }
+    at = start + data.len();
+  }
+  Ok(map)
+}
@@ -0,0 +1,110 @@
+use std::{
+  future::{Future, ready},
+  io,
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+  time::{Duration, Instant},
+};
+
+use jdb_base::{
+  Flag, Mem as _, Pos,
+  ckp::sst::Meta,
+  sst::{Kv, MemToSst, RangeDel},
+};
+use jdb_mem::Mem;
+
+mod common;
+
+use common::{MockDiscard, meta};
+
+/// Counts pushed SSTs
+/// 统计已推送的 SST
+#[derive(Default)]
+struct CountSst(Arc<AtomicUsize>);
+
+impl MemToSst for CountSst {
+  type Error = io::Error;
+
+  fn write<'a>(
+    &self,
+    _iter: impl Iterator<Item = Kv<'a>>,
+    _range_del: impl Iterator<Item = RangeDel<'a>>,
+  ) -> impl Future<Output = Result<Meta, Self::Error>> {
+    ready(Ok(meta(0)))
+  }
+
+  fn push(&mut self, _meta: Meta, _wal_id: u64) {
+    self.0.fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+const POS: Pos = Pos::new(1, Flag::INFILE, 0, 0, 1);
+const AGE: Duration = Duration::from_millis(20);
+
+fn mem() -> (Mem<CountSst, MockDiscard>, Arc<AtomicUsize>) {
+  let sst = CountSst::default();
+  let pushed = sst.0.clone();
+  let mut mem = Mem::new(usize::MAX, sst, MockDiscard);
+  mem.max_age = Some(AGE);
+  (mem, pushed)
+}
+
+#[compio::test]
+async fn test_due() {
+  let (mut mem, pushed) = mem();
+  assert_eq!(mem.deadline(), None);
+  // Empty map: the timer fires but nothing rotates
+  // 空 Map：定时器触发但不轮转
+  mem.due().await;
+  assert!(!mem.maybe_rotate().unwrap());
+
+  // Writes go on while the timer waits
+  // 定时器等待期间写入照常进行
+  let start = Instant::now();
+  let due = mem.due();
+  mem.put(b"a".as_slice(), POS).await.unwrap();
+  assert!(mem.deadline().is_some());
+  due.await;
+  if !mem.maybe_rotate().unwrap() {
+    mem.due().await;
+    assert!(mem.maybe_rotate().unwrap());
+  }
+  assert!(start.elapsed() >= AGE);
+  assert_eq!(mem.deadline(), None);
+
+  mem.sync().await.unwrap();
+  assert_eq!(pushed.load(Ordering::Relaxed), 1);
+  assert_eq!(mem.get(b"a".as_slice()), None);
+}
+
+#[compio::test]
+async fn test_old_on_write() {
+  let (mut mem, _) = mem();
+  mem.put(b"a".as_slice(), POS).await.unwrap();
+  compio::time::sleep(AGE).await;
+
+  // The next write finds the map too old and rotates first
+  // 下一次写入发现 Map 过老，先轮转
+  mem.put(b"b".as_slice(), POS).await.unwrap();
+  assert_eq!(mem.freeze.len(), 1);
+  assert_eq!(mem.now.get(b"b".as_slice()), Some(POS));
+  assert_eq!(mem.now.get(b"a".as_slice()), None);
+}
+
+#[compio::test]
+async fn test_flush() {
+  let (mut mem, pushed) = mem();
+  mem.max_age = None;
+  mem.put(b"a".as_slice(), POS).await.unwrap();
+  mem.flush().await.unwrap();
+  assert!(mem.freeze.is_empty());
+  assert_eq!(mem.born, None);
+  assert_eq!(pushed.load(Ordering::Relaxed), 1);
+
+  // Nothing to flush, no empty SST
+  // 无内容可刷，不产生空 SST
+  mem.flush().await.unwrap();
+  assert_eq!(pushed.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,61 @@
+use jdb_base::{Flag, Mem as _, Pos};
+use jdb_mem::{Mem, Table as _, WriteBatch};
+
+mod common;
+
+use common::{MockDiscard, MockSst};
+
+fn pos(ver: u64) -> Pos {
+  Pos {
+    ver,
+    wal_id: 0,
+    offset_or_file_id: ver,
+    len: 1,
+    flag: Flag::INFILE,
+  }
+}
+
+#[test]
+fn test_batch_encode() {
+  let mut batch = WriteBatch::new();
+  batch
+    .put(b"a", pos(1))
+    .delete(b"bb", pos(2))
+    .put(b"", pos(3));
+  assert_eq!(batch.len(), 3);
+
+  let li: Vec<_> = batch.iter().map(|(k, p)| (k.to_vec(), p)).collect();
+  assert_eq!(li[1].0, b"bb".to_vec());
+  assert!(li[1].1.flag.is_tombstone());
+  assert!(li[2].0.is_empty());
+
+  let bin = batch.encode();
+  assert_eq!(WriteBatch::decode(&bin).unwrap(), batch);
+
+  batch.clear();
+  assert!(batch.is_empty());
+}
+
+#[compio::test]
+async fn test_batch_no_straddle() {
+  // Rotate as soon as the active map holds anything
+  // 活跃 Map 一有内容就轮转
+  let mut mem = Mem::new(1, MockSst, MockDiscard);
+  mem.put(b"x".as_slice(), pos(1)).await.unwrap();
+
+  let mut batch = WriteBatch::new();
+  batch
+    .put(b"a", pos(2))
+    .put(b"b", pos(3))
+    .delete(b"x", pos(4))
+    .put(b"a", pos(5));
+  mem.write(&batch).await.unwrap();
+
+  // Rotated once up front, whole batch landed in the new active map
+  // 预先轮转一次，整个批次落入新的活跃 Map
+  assert_eq!(mem.now.table().len(), 3);
+  assert_eq!(mem.now.get(b"a".as_slice()), Some(pos(5)));
+  assert!(mem.get(b"x".as_slice()).unwrap().flag.is_tombstone());
+  assert_eq!(mem.now.discards().count(), 1);
+  assert_eq!(mem.size, mem.now.size());
+}
@@ -0,0 +1,83 @@
+use jdb_base::{Flag, Mem as _, Pos};
+use jdb_mem::{Mem, WriteBuffer};
+
+mod common;
+
+use common::{MockDiscard, MockSst};
+
+const POS: Pos = Pos::new(1, Flag::INFILE, 0, 0, 1);
+
+fn mem(buf: &WriteBuffer) -> Mem<MockSst, MockDiscard> {
+  let mut mem = Mem::new(usize::MAX, MockSst, MockDiscard);
+  mem.buffer = Some(buf.register());
+  mem
+}
+
+#[test]
+fn test_budget() {
+  let buf = WriteBuffer::new(100);
+  let a = buf.register();
+  let b = buf.register();
+  a.set(50);
+  b.set(30);
+  assert_eq!(buf.used(), 80);
+  assert!(!a.take_force());
+
+  // Active maps reach 7/8: the largest is forced, only once until it rotates
+  // 活跃 Map 达到 7/8：强制最大的那个，在它轮转前只强制一次
+  b.set(45);
+  assert_eq!(buf.used(), 95);
+  b.set(48);
+  assert!(!b.take_force());
+
+  // An idle forced member does not hold up the budget, the next largest is picked
+  // 空闲的被强制成员不会卡住预算，改为挑选次大的成员
+  b.set(90);
+  assert!(b.take_force());
+  assert!(a.take_force());
+  assert!(!a.take_force());
+
+  a.set(0);
+  assert_eq!(buf.used(), 90);
+  drop(b);
+  assert_eq!(buf.used(), 0);
+}
+
+#[compio::test]
+async fn test_force_rotate() {
+  let buf = WriteBuffer::new(usize::MAX);
+  let mut big = mem(&buf);
+  let mut small = mem(&buf);
+  // Both fit one arena chunk, the overwrite's discard makes big the larger
+  // 两者都只占一个竞技场块，覆盖产生的丢弃项使 big 更大
+  for k in [b"a", b"b", b"c", b"a"] {
+    big.put(k.as_slice(), POS).await.unwrap();
+  }
+  small.put(b"x".as_slice(), POS).await.unwrap();
+  assert_eq!(buf.used(), big.size + small.size);
+
+  // Lower the limit: the quiet largest table rotates from its background loop
+  // 降低上限：安静的最大表在其后台循环中轮转
+  buf.set_limit(buf.used());
+  big.due().await;
+  assert!(big.maybe_rotate().unwrap());
+  assert_eq!(big.freeze.len(), 1);
+  assert_eq!(big.size, 0);
+
+  // The frozen map stays in the budget until its flush completes
+  // 冻结 Map 留在预算中，直到其刷盘完成
+  let frozen = big.freeze[0].size();
+  assert_eq!(buf.used(), frozen + small.size);
+  big.sync().await.unwrap();
+  assert_eq!(buf.used(), small.size);
+
+  // Next overflow picks small, which rotates on its own next write
+  // 下一次超限挑中 small，它在自己的下一次写入时轮转
+  buf.set_limit(small.size);
+  small.put(b"y".as_slice(), POS).await.unwrap();
+  assert_eq!(small.freeze.len(), 1);
+  assert_eq!(small.now.get(b"y".as_slice()), Some(POS));
+
+  big.sync().await.unwrap();
+  small.sync().await.unwrap();
+}
@@ -0,0 +1,94 @@
+//! Fixtures shared by the integration tests
+//! 集成测试共享的测试夹具
+
+#![allow(dead_code)]
+
+use std::{
+  future::{Future, ready},
+  io,
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+};
+
+use jdb_base::{
+  Discard, Pos,
+  ckp::sst::{Meta, Sst},
+  sst::{self, Kv, Level, MemToSst, RangeDel},
+};
+
+#[static_init::constructor(0)]
+extern "C" fn _log_init() {
+  log_init::init();
+}
+
+/// Empty L0 SST named id
+/// 名为 id 的空 L0 SST
+pub fn meta(id: u64) -> Meta {
+  Meta {
+    sst: Sst {
+      level: Level::L0,
+      rmed: 0,
+      size: 0,
+    },
+    meta: sst::Meta::new(id),
+  }
+}
+
+/// Flush that keeps nothing
+/// 不保留任何内容的刷盘
+#[derive(Debug)]
+pub struct MockSst;
+
+impl MemToSst for MockSst {
+  type Error = io::Error;
+
+  fn write<'a>(
+    &self,
+    _iter: impl Iterator<Item = Kv<'a>>,
+    _range_del: impl Iterator<Item = RangeDel<'a>>,
+  ) -> impl Future<Output = Result<Meta, Self::Error>> {
+    ready(Ok(meta(0)))
+  }
+
+  fn push(&mut self, _meta: Meta, _wal_id: u64) {}
+}
+
+/// Fails the first `n` writes
+/// 前 `n` 次写入失败
+pub struct FailSst(pub Arc<AtomicUsize>);
+
+impl MemToSst for FailSst {
+  type Error = io::Error;
+
+  fn write<'a>(
+    &self,
+    _iter: impl Iterator<Item = Kv<'a>>,
+    _range_del: impl Iterator<Item = RangeDel<'a>>,
+  ) -> impl Future<Output = Result<Meta, Self::Error>> {
+    let left = self.0.load(Ordering::Relaxed);
+    if left > 0 {
+      self.0.store(left - 1, Ordering::Relaxed);
+      return ready(Err(io::Error::other("disk full")));
+    }
+    ready(Ok(meta(0)))
+  }
+
+  fn push(&mut self, _meta: Meta, _wal_id: u64) {}
+}
+
+/// Discard that keeps nothing
+/// 不保留任何内容的丢弃
+#[derive(Debug)]
+pub struct MockDiscard;
+
+impl Discard for MockDiscard {
+  type Error = io::Error;
+
+  fn discard(&mut self, _key: &[u8], _pos: &Pos) {}
+
+  fn flush(&mut self) -> impl Future<Output = Result<(), Self::Error>> {
+    ready(Ok(()))
+  }
+}
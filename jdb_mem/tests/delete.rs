@@ -0,0 +1,131 @@
+use std::{
+  future::{Future, ready},
+  io,
+  sync::{Arc, Mutex},
+};
+
+use jdb_base::{
+  Flag, Mem as _, Pos,
+  ckp::sst::Meta,
+  sst::{Kv, MemToSst, RangeDel},
+};
+use jdb_mem::Mem;
+
+mod common;
+
+use common::{MockDiscard, meta};
+
+type Rec = Arc<Mutex<Vec<(Vec<u8>, Vec<u8>, u64)>>>;
+
+/// Records range tombstones handed to each flush
+/// 记录每次刷盘收到的范围墓碑
+#[derive(Default)]
+struct RecSst(Rec);
+
+impl MemToSst for RecSst {
+  type Error = io::Error;
+
+  fn write<'a>(
+    &self,
+    _iter: impl Iterator<Item = Kv<'a>>,
+    range_del: impl Iterator<Item = RangeDel<'a>>,
+  ) -> impl Future<Output = Result<Meta, Self::Error>> {
+    self
+      .0
+      .lock()
+      .unwrap()
+      .extend(range_del.map(|(start, end, pos)| (start.to_vec(), end.to_vec(), pos.ver)));
+    ready(Ok(meta(0)))
+  }
+
+  fn push(&mut self, _meta: Meta, _wal_id: u64) {}
+}
+
+fn pos(ver: u64) -> Pos {
+  Pos::new(ver, Flag::INFILE, 0, ver, 1)
+}
+
+fn keys<'a>(iter: impl Iterator<Item = (&'a [u8], Pos)>) -> Vec<(Vec<u8>, bool)> {
+  iter.map(|(k, p)| (k.to_vec(), p.is_tombstone())).collect()
+}
+
+#[compio::test]
+async fn test_delete() {
+  let mut mem = Mem::new(usize::MAX, RecSst::default(), MockDiscard);
+  mem.put(b"a".as_slice(), pos(1)).await.unwrap();
+  mem.delete(b"a".as_slice(), pos(2)).await.unwrap();
+
+  let got = mem.get(b"a".as_slice()).unwrap();
+  assert!(got.is_tombstone());
+  assert_eq!(got.ver, 2);
+  assert_eq!(mem.now.discards().count(), 1);
+}
+
+#[compio::test]
+async fn test_delete_range() {
+  let rec = RecSst::default();
+  let flushed = rec.0.clone();
+  let mut mem = Mem::new(usize::MAX, rec, MockDiscard);
+  for (i, k) in [b"a", b"b", b"c", b"d"].into_iter().enumerate() {
+    mem.put(k.as_slice(), pos(i as u64 + 1)).await.unwrap();
+  }
+
+  // Older layer: range delete then freeze
+  // 较老的层：范围删除后冻结
+  mem
+    .delete_range(b"b".as_slice()..b"d".as_slice(), pos(5))
+    .await
+    .unwrap();
+  assert!(mem.get(b"b".as_slice()).unwrap().is_tombstone());
+  assert!(mem.get(b"c".as_slice()).unwrap().is_tombstone());
+  assert!(!mem.get(b"d".as_slice()).unwrap().is_tombstone());
+  // Range tombstone answers for keys never written
+  // 从未写入的键也由范围墓碑应答
+  assert_eq!(mem.get(b"bz".as_slice()).map(|p| p.ver), Some(5));
+  assert_eq!(mem.get(b"e".as_slice()), None);
+  mem.rotate().unwrap();
+
+  // Newer layer: rewrite c after the range delete
+  // 较新的层：在范围删除之后重写 c
+  mem.put(b"c".as_slice(), pos(6)).await.unwrap();
+  assert_eq!(mem.get(b"c".as_slice()), Some(pos(6)));
+  assert!(mem.get(b"b".as_slice()).unwrap().is_tombstone());
+
+  let t = |k: &[u8], del: bool| (k.to_vec(), del);
+  let want = [
+    t(b"a", false),
+    t(b"b", true),
+    t(b"c", false),
+    t(b"d", false),
+  ];
+  assert_eq!(keys(mem.iter()), want);
+  let mut rev = want.to_vec();
+  rev.reverse();
+  assert_eq!(keys(mem.rev_iter()), rev);
+  assert_eq!(
+    keys(mem.range(b"b".as_slice()..b"d".as_slice())),
+    &want[1..3]
+  );
+
+  // Range delete in the newest layer masks older layers too
+  // 最新层的范围删除同样遮蔽更老的层
+  mem
+    .delete_range(b"a".as_slice()..b"c".as_slice(), pos(7))
+    .await
+    .unwrap();
+  assert_eq!(mem.get(b"a".as_slice()).map(|p| p.ver), Some(7));
+  assert_eq!(
+    keys(mem.iter()),
+    [t(b"a", true), t(b"b", true), t(b"c", false), t(b"d", false)]
+  );
+
+  mem.rotate().unwrap();
+  mem.sync().await.unwrap();
+  assert_eq!(
+    *flushed.lock().unwrap(),
+    [
+      (b"b".to_vec(), b"d".to_vec(), 5),
+      (b"a".to_vec(), b"c".to_vec(), 7)
+    ]
+  );
+}
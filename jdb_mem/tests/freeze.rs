@@ -0,0 +1,96 @@
+use std::{
+  future::Future,
+  io,
+  sync::{Arc, Mutex},
+  time::Duration,
+};
+
+use jdb_base::{
+  Flag, Mem as _, Pos,
+  ckp::sst::Meta,
+  sst::{Kv, MemToSst, RangeDel},
+};
+use jdb_mem::Mem;
+
+mod common;
+
+use common::{MockDiscard, meta};
+
+/// Slow flush, records the first key of each pushed map
+/// 慢速刷盘，记录每个已推送 Map 的首个键
+#[derive(Default)]
+struct SlowSst(Arc<Mutex<Vec<u64>>>);
+
+impl MemToSst for SlowSst {
+  type Error = io::Error;
+
+  fn write<'a>(
+    &self,
+    mut iter: impl Iterator<Item = Kv<'a>>,
+    _range_del: impl Iterator<Item = RangeDel<'a>>,
+  ) -> impl Future<Output = Result<Meta, Self::Error>> {
+    let id = iter.next().map(|(k, _)| k[0] as u64).unwrap_or(0);
+    async move {
+      compio::time::sleep(Duration::from_millis(30)).await;
+      Ok(meta(id))
+    }
+  }
+
+  fn push(&mut self, meta: Meta, _wal_id: u64) {
+    self.0.lock().unwrap().push(meta.meta.id);
+  }
+}
+
+fn pos(ver: u64) -> Pos {
+  Pos {
+    ver,
+    wal_id: 0,
+    offset_or_file_id: 0,
+    len: 1,
+    flag: Flag::INFILE,
+  }
+}
+
+#[compio::test]
+async fn test_freeze_queue() {
+  let sst = SlowSst::default();
+  let pushed = sst.0.clone();
+  // Rotate on every put
+  // 每次 put 都轮转
+  let mut mem = Mem::new(1, sst, MockDiscard);
+  mem.max_freeze = 3;
+
+  mem.put(b"a".as_slice(), pos(1)).await.unwrap();
+  mem.put(b"b".as_slice(), pos(1)).await.unwrap();
+  mem.put(b"a".as_slice(), pos(2)).await.unwrap();
+  // Both rotations queued without waiting for flush
+  // 两次轮转都入队，无需等待刷盘
+  assert_eq!(mem.freeze.len(), 2);
+  assert!(pushed.lock().unwrap().is_empty());
+
+  assert_eq!(mem.get(b"a".as_slice()), Some(pos(2)));
+  assert_eq!(mem.get(b"b".as_slice()), Some(pos(1)));
+  let li: Vec<_> = mem.iter().map(|(k, p)| (k.to_vec(), p.ver)).collect();
+  assert_eq!(li, vec![(b"a".to_vec(), 2), (b"b".to_vec(), 1)]);
+  let li: Vec<_> = mem.rev_iter().map(|(k, p)| (k.to_vec(), p.ver)).collect();
+  assert_eq!(li, vec![(b"b".to_vec(), 1), (b"a".to_vec(), 2)]);
+  let li: Vec<_> = mem
+    .range(b"b".as_slice()..)
+    .map(|(k, _)| k.to_vec())
+    .collect();
+  assert_eq!(li, vec![b"b".to_vec()]);
+
+  mem.sync().await.unwrap();
+  assert!(mem.freeze.is_empty());
+  assert_eq!(*pushed.lock().unwrap(), vec![b'a' as u64, b'b' as u64]);
+
+  // Full queue waits for the oldest flush only
+  // 队列满时只等待最老的刷盘
+  mem.max_freeze = 1;
+  mem.put(b"c".as_slice(), pos(1)).await.unwrap();
+  assert_eq!(mem.freeze.len(), 1);
+  mem.put(b"d".as_slice(), pos(1)).await.unwrap();
+  assert_eq!(mem.freeze.len(), 1);
+  assert_eq!(pushed.lock().unwrap().len(), 3);
+  assert_eq!(mem.get(b"c".as_slice()), Some(pos(1)));
+}
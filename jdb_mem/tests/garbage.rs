@@ -0,0 +1,105 @@
+use std::{
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+  time::Duration,
+};
+
+use aok::{OK, Void};
+use jdb_base::{Discard, Flag, Mem as _, Pos};
+use jdb_mem::{Garbage, Mem, Retry};
+use tempfile::tempdir;
+
+mod common;
+
+use common::FailSst;
+
+fn pos(ver: u64, wal_id: u64, len: u32) -> Pos {
+  Pos::new(ver, Flag::INFILE, wal_id, ver, len)
+}
+
+fn at(ver: u64, wal_id: u64, offset: u64, len: u32) -> Pos {
+  Pos::new(ver, Flag::INFILE, wal_id, offset, len)
+}
+
+#[compio::test]
+async fn test_garbage() -> Void {
+  let dir = tempdir()?;
+  let path = dir.path().join("garbage");
+
+  let mut g = Garbage::open(&path).await?;
+  g.discard(b"a", &pos(1, 1, 100));
+  g.discard(b"b", &pos(2, 1, 50));
+  g.discard(b"c", &pos(3, 2, 10));
+  // Values in separate files are not WAL garbage
+  // 独立文件中的值不是 WAL 垃圾
+  g.discard(b"d", &Pos::new(4, Flag::FILE, 2, 9, 1000));
+  assert_eq!(g.dead(1), 150);
+  assert_eq!(g.dead(2), 10);
+  assert_eq!(g.garbage_ratio(1), None);
+
+  g.set_size(1, 300);
+  g.set_size(2, 100);
+  g.set_size(3, 100);
+  // Tracked with nothing known yet, not a removal
+  // 已跟踪但尚无任何已知信息，不是删除
+  g.set_size(4, 0);
+  assert_eq!(g.garbage_ratio(1), Some(0.5));
+  assert_eq!(g.garbage_ratio(2), Some(0.1));
+  assert_eq!(g.garbage_ratio(3), Some(0.0));
+  assert_eq!(g.dirtiest(), Some((1, 0.5)));
+  g.flush().await?;
+  drop(g);
+
+  // Totals survive reopen, removed files are forgotten
+  // 累计值在重新打开后保留，已删除的文件被遗忘
+  let mut g = Garbage::open(&path).await?;
+  assert_eq!(g.dead(1), 150);
+  assert_eq!(g.garbage_ratio(2), Some(0.1));
+  assert_eq!(g.get(4).map(|h| h.size), Some(0));
+  g.rm(1);
+  g.flush().await?;
+  drop(g);
+
+  let g = Garbage::open(&path).await?;
+  assert_eq!(g.dead(1), 0);
+  assert!(g.get(1).is_none());
+  assert!(g.get(4).is_some());
+  assert_eq!(g.garbage_ratio(1), None);
+  assert_eq!(g.dirtiest(), Some((2, 0.1)));
+  OK
+}
+
+#[compio::test]
+async fn test_garbage_from_mem() -> Void {
+  let dir = tempdir()?;
+  let path = dir.path().join("garbage");
+
+  // The first flush fails and is retried, its garbage must count once
+  // 第一次刷盘失败并重试，其垃圾只能计一次
+  let fail = Arc::new(AtomicUsize::new(1));
+  let sst = FailSst(fail.clone());
+  let mut mem = Mem::new(usize::MAX, sst, Garbage::open(&path).await?);
+  mem.retry = Retry {
+    base: Duration::from_millis(1),
+    max_delay: Duration::from_millis(1),
+    max_attempts: 3,
+  };
+  mem.put(b"a".as_slice(), at(1, 7, 0, 40)).await?;
+  mem.put(b"a".as_slice(), at(2, 7, 40, 60)).await?;
+  mem.put(b"b".as_slice(), at(3, 7, 100, 100)).await?;
+  mem.delete(b"a".as_slice(), at(4, 8, 0, 5)).await?;
+  mem.flush().await?;
+  assert_eq!(fail.load(Ordering::Relaxed), 0);
+  drop(mem);
+
+  // Both overwritten values of `a` are dead in value file 7, which is 200 bytes so far
+  // `a` 的两个被覆盖值在值文件 7 中都已死亡，该文件目前为 200 字节
+  let g = Garbage::open(&path).await?;
+  assert_eq!(g.dead(7), 100);
+  assert_eq!(g.get(7).map(|h| h.size), Some(200));
+  assert_eq!(g.garbage_ratio(7), Some(0.5));
+  assert_eq!(g.dead(8), 0);
+  OK
+}
@@ -0,0 +1,96 @@
+use std::{
+  cell::RefCell,
+  rc::Rc,
+  sync::{Arc, atomic::AtomicUsize},
+  time::Duration,
+};
+
+use jdb_base::{Flag, Mem as _, Pos};
+use jdb_mem::{Error, Mem, Retry, RetryEvent};
+
+mod common;
+
+use common::{FailSst, MockDiscard};
+
+const POS: Pos = Pos::new(1, Flag::INFILE, 0, 0, 1);
+
+const RETRY: Retry = Retry {
+  base: Duration::from_millis(1),
+  max_delay: Duration::from_millis(3),
+  max_attempts: 3,
+};
+
+/// (attempt, delay), delay is None once given up
+/// （尝试次数, 延迟），放弃时延迟为 None
+type Log = Rc<RefCell<Vec<(u32, Option<Duration>)>>>;
+
+/// Mem whose flush fails `n` times
+/// 刷盘失败 `n` 次的 Mem
+fn mem(n: usize) -> (Mem<FailSst, MockDiscard>, Log) {
+  let mut mem = Mem::new(
+    usize::MAX,
+    FailSst(Arc::new(AtomicUsize::new(n))),
+    MockDiscard,
+  );
+  mem.retry = RETRY;
+  let log = Log::default();
+  let li = log.clone();
+  mem.on_retry = Some(Box::new(move |e| {
+    li.borrow_mut().push(match e {
+      RetryEvent::Retry { attempt, delay, .. } => (attempt, Some(delay)),
+      RetryEvent::GiveUp { attempt, .. } => (attempt, None),
+    })
+  }));
+  (mem, log)
+}
+
+#[test]
+fn test_delay() {
+  let li: Vec<_> = (1..=4).map(|i| RETRY.delay(i).as_millis()).collect();
+  assert_eq!(li, [1, 2, 3, 3]);
+  assert!(!RETRY.give_up(2));
+  assert!(RETRY.give_up(3));
+  assert!(
+    !Retry {
+      max_attempts: 0,
+      ..RETRY
+    }
+    .give_up(u32::MAX)
+  );
+}
+
+#[compio::test]
+async fn test_retry_recovers() {
+  let (mut mem, log) = mem(2);
+  mem.put(b"a".as_slice(), POS).await.unwrap();
+  mem.rotate().unwrap();
+  mem.sync().await.unwrap();
+
+  assert!(mem.freeze.is_empty());
+  assert!(mem.bg_error.is_none());
+  let ms = |n| Some(Duration::from_millis(n));
+  // First failure came from the flush started by rotate
+  // 第一次失败来自 rotate 启动的刷盘
+  assert_eq!(*log.borrow(), [(1, ms(1)), (2, ms(2))]);
+}
+
+#[compio::test]
+async fn test_retry_read_only() {
+  let (mut mem, log) = mem(usize::MAX);
+  mem.put(b"a".as_slice(), POS).await.unwrap();
+  mem.rotate().unwrap();
+
+  assert!(matches!(mem.sync().await, Err(Error::ReadOnly)));
+  assert_eq!(log.borrow().last(), Some(&(3, None)));
+  assert!(matches!(mem.bg_error, Some(Error::Sst(_))));
+
+  // Sticky: writes fail, reads still work
+  // 粘滞：写入失败，读取仍可用
+  assert!(matches!(
+    mem.put(b"b".as_slice(), POS).await,
+    Err(Error::ReadOnly)
+  ));
+  assert!(matches!(mem.sync().await, Err(Error::ReadOnly)));
+  assert_eq!(mem.get(b"a".as_slice()), Some(POS));
+  assert_eq!(log.borrow().len(), 3);
+}
@@ -0,0 +1,104 @@
+use std::{
+  alloc::{GlobalAlloc, Layout, System},
+  cell::Cell,
+};
+
+use jdb_base::{Flag, Pos};
+use jdb_mem::{Art, BTree, Map, Skip, Table};
+
+/// Counts live bytes allocated by the current thread
+/// 统计当前线程分配的存活字节数
+struct Count;
+
+thread_local! {
+  static LIVE: Cell<isize> = const { Cell::new(0) };
+}
+
+fn add(n: isize) {
+  // try_with: the slot may be gone while the thread exits
+  // try_with：线程退出时该槽位可能已销毁
+  let _ = LIVE.try_with(|live| live.set(live.get() + n));
+}
+
+unsafe impl GlobalAlloc for Count {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    add(layout.size() as isize);
+    unsafe { System.alloc(layout) }
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    add(-(layout.size() as isize));
+    unsafe { System.dealloc(ptr, layout) }
+  }
+
+  unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+    add(new_size as isize - layout.size() as isize);
+    unsafe { System.realloc(ptr, layout, new_size) }
+  }
+}
+
+#[global_allocator]
+static ALLOC: Count = Count;
+
+fn live() -> isize {
+  LIVE.with(Cell::get)
+}
+
+fn pos(ver: u64) -> Pos {
+  Pos {
+    ver,
+    wal_id: ver % 3,
+    offset_or_file_id: ver * 10,
+    len: 10,
+    flag: Flag::INFILE,
+  }
+}
+
+/// Sequential, random, long and prefix keys with overwrites, grow every node kind
+/// 顺序、随机、长键和前缀键，并带覆盖写，使每种节点都增长
+fn keys() -> Vec<Vec<u8>> {
+  let mut rng = fastrand::Rng::with_seed(11);
+  let mut li = Vec::new();
+  for i in 0..20_000u32 {
+    li.push(match i % 4 {
+      0 => i.to_be_bytes().to_vec(),
+      1 => (0..rng.usize(0..8)).map(|_| rng.u8(b'a'..b'f')).collect(),
+      2 => vec![b'w', rng.u8(..), rng.u8(..)],
+      _ => vec![b'l'; rng.usize(100..20_000)],
+    });
+  }
+  li
+}
+
+fn check<T: Table>() {
+  let keys = keys();
+  let base = live();
+  let mut map = Map::<T>::new();
+  assert_eq!(map.size(), 0);
+  for (ver, k) in keys.iter().enumerate() {
+    let ver = ver as u64;
+    map.put(k, pos(ver));
+    if ver.is_multiple_of(1000) {
+      map.delete_range(k, b"z", pos(ver));
+    }
+    assert_eq!((live() - base) as usize, map.size());
+  }
+  assert!(map.discards().count() > 0);
+  drop(map);
+  assert_eq!(live(), base);
+}
+
+#[test]
+fn test_btree() {
+  check::<BTree>();
+}
+
+#[test]
+fn test_skip() {
+  check::<Skip>();
+}
+
+#[test]
+fn test_art() {
+  check::<Art>();
+}
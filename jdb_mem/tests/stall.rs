@@ -0,0 +1,45 @@
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use jdb_base::{
+  Flag, Mem as _, Pos,
+  stall::{Reason, Stall},
+};
+use jdb_mem::Mem;
+
+mod common;
+
+use common::{MockDiscard, MockSst};
+
+const POS: Pos = Pos {
+  ver: 1,
+  wal_id: 0,
+  offset_or_file_id: 0,
+  len: 1,
+  flag: Flag::INFILE,
+};
+
+#[compio::test]
+async fn test_put_stop() {
+  let mut mem = Mem::new(1 << 20, MockSst, MockDiscard);
+  let signal = mem.signal.clone();
+  signal.set(Stall::Stop(Reason::PendingBytes));
+  assert_eq!(mem.stall(), Stall::Stop(Reason::PendingBytes));
+
+  let done = Rc::new(Cell::new(false));
+  let task = {
+    let done = done.clone();
+    compio::runtime::spawn(async move {
+      mem.put(b"k".as_slice(), POS).await.unwrap();
+      done.set(true);
+      mem
+    })
+  };
+
+  compio::time::sleep(Duration::from_millis(20)).await;
+  assert!(!done.get());
+
+  signal.set(Stall::Delay(Reason::L0Files));
+  let mem = task.await.unwrap();
+  assert!(done.get());
+  assert_eq!(mem.get(b"k".as_slice()), Some(POS));
+}
@@ -0,0 +1,150 @@
+use std::{collections::BTreeMap, ops::Bound};
+
+use jdb_base::{Flag, Pos};
+use jdb_mem::{Art, BTree, Map, Skip, Table};
+
+fn pos(ver: u64) -> Pos {
+  Pos {
+    ver,
+    wal_id: 0,
+    offset_or_file_id: 0,
+    len: 1,
+    flag: Flag::INFILE,
+  }
+}
+
+/// Short keys over a tiny alphabet: many shared prefixes and prefix keys
+/// 小字母表上的短键：大量共享前缀和互为前缀的键
+fn key(rng: &mut fastrand::Rng) -> Vec<u8> {
+  let len = rng.usize(0..6);
+  (0..len).map(|_| rng.u8(b'a'..b'e')).collect()
+}
+
+/// Wide fanout keys, grow ART nodes up to 256 children
+/// 高扇出的键，使 ART 节点增长到 256 个子节点
+fn wide(rng: &mut fastrand::Rng) -> Vec<u8> {
+  vec![b'w', rng.u8(..), rng.u8(..)]
+}
+
+fn bound(rng: &mut fastrand::Rng) -> Bound<Vec<u8>> {
+  match rng.u8(0..3) {
+    0 => Bound::Unbounded,
+    1 => Bound::Included(key(rng)),
+    _ => Bound::Excluded(key(rng)),
+  }
+}
+
+fn as_ref(b: &Bound<Vec<u8>>) -> Bound<&[u8]> {
+  b.as_ref().map(|k| k.as_slice())
+}
+
+fn check<T: Table>() {
+  let mut rng = fastrand::Rng::with_seed(7);
+  let mut map = Map::<T>::new();
+  let mut expect = BTreeMap::new();
+  let mut discards = Vec::new();
+  let mut key_bytes = 0;
+
+  for ver in 0..3000u64 {
+    let k = if ver % 3 == 0 {
+      wide(&mut rng)
+    } else {
+      key(&mut rng)
+    };
+    map.put(&k, pos(ver));
+    key_bytes += k.len();
+    if let Some(old) = expect.insert(k.clone(), pos(ver)) {
+      discards.push((k, old));
+    }
+  }
+  let table = map.table();
+  assert_eq!(table.len(), expect.len());
+  let got: Vec<_> = map.discards().map(|(k, p)| (k.to_vec(), p)).collect();
+  assert_eq!(got, discards);
+  // Key bytes and discards are part of the size, tests/size.rs checks it exactly
+  // 键字节和丢弃项都计入大小，tests/size.rs 会精确检查
+  assert!(map.size() >= key_bytes + discards.len() * Map::<T>::ENTRY_SIZE);
+
+  for _ in 0..500 {
+    let k = key(&mut rng);
+    assert_eq!(table.get(&k), expect.get(&k).copied());
+  }
+
+  let all: Vec<_> = expect.iter().map(|(k, p)| (k.as_slice(), *p)).collect();
+  let got: Vec<_> = table.range(Bound::Unbounded, Bound::Unbounded).collect();
+  assert_eq!(got, all);
+
+  for _ in 0..500 {
+    let (start, end) = (bound(&mut rng), bound(&mut rng));
+    // BTreeMap::range panics on inverted bounds
+    // BTreeMap::range 在边界颠倒时会 panic
+    if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+      (&start, &end)
+      && (s > e || (s == e && !matches!((&start, &end), (Bound::Included(_), Bound::Included(_)))))
+    {
+      continue;
+    }
+    let in_range = |k: &[u8]| {
+      (match &start {
+        Bound::Included(s) => k >= s.as_slice(),
+        Bound::Excluded(s) => k > s.as_slice(),
+        Bound::Unbounded => true,
+      }) && (match &end {
+        Bound::Included(e) => k <= e.as_slice(),
+        Bound::Excluded(e) => k < e.as_slice(),
+        Bound::Unbounded => true,
+      })
+    };
+    let want: Vec<_> = all.iter().filter(|(k, _)| in_range(k)).copied().collect();
+    let got: Vec<_> = table.range(as_ref(&start), as_ref(&end)).collect();
+    assert_eq!(got, want, "range {start:?} {end:?}");
+    let got: Vec<_> = table.rev_range(as_ref(&start), as_ref(&end)).collect();
+    let want: Vec<_> = want.into_iter().rev().collect();
+    assert_eq!(got, want, "rev_range {start:?} {end:?}");
+  }
+}
+
+#[test]
+fn test_btree() {
+  check::<BTree>();
+}
+
+#[test]
+fn test_skip() {
+  check::<Skip>();
+}
+
+#[test]
+fn test_art() {
+  check::<Art>();
+}
+
+/// Ascending and descending runs split at the ends of nodes
+/// 升序和降序写入在节点两端分裂
+fn check_seq<T: Table>() {
+  let mut map = Map::<T>::new();
+  for i in (0..2000u32).chain((2000..4000).rev()) {
+    map.put(&i.to_be_bytes(), pos(i as u64));
+  }
+  let table = map.table();
+  let got: Vec<_> = table
+    .range(Bound::Unbounded, Bound::Unbounded)
+    .map(|(_, p)| p.ver)
+    .collect();
+  assert_eq!(got, (0..4000).collect::<Vec<_>>());
+  let got: Vec<_> = table
+    .rev_range(
+      Bound::Excluded(&10u32.to_be_bytes()),
+      Bound::Included(&3990u32.to_be_bytes()),
+    )
+    .map(|(_, p)| p.ver)
+    .collect();
+  assert_eq!(got, (11..=3990).rev().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_seq() {
+  check_seq::<BTree>();
+  check_seq::<Skip>();
+  check_seq::<Art>();
+}
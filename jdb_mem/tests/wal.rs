@@ -0,0 +1,136 @@
+use std::{
+  future::{Future, ready},
+  io::{self, Write},
+  sync::{Arc, Mutex},
+};
+
+use aok::{OK, Void};
+use jdb_base::{
+  Flag, Mem as _, Pos,
+  ckp::sst::Meta,
+  sst::{Kv, MemToSst, RangeDel},
+};
+use jdb_mem::{Mem, Table as _, WriteBatch};
+use tempfile::tempdir;
+
+mod common;
+
+use common::{MockDiscard, meta};
+
+/// Records the wal_id of each pushed map, like `Levels::wal_id`
+/// 记录每个已推送 Map 的 wal_id，同 `Levels::wal_id`
+#[derive(Default)]
+struct MockSst(Arc<Mutex<Vec<u64>>>);
+
+impl MemToSst for MockSst {
+  type Error = io::Error;
+
+  fn write<'a>(
+    &self,
+    _iter: impl Iterator<Item = Kv<'a>>,
+    _range_del: impl Iterator<Item = RangeDel<'a>>,
+  ) -> impl Future<Output = Result<Meta, Self::Error>> {
+    ready(Ok(meta(0)))
+  }
+
+  fn push(&mut self, _meta: Meta, wal_id: u64) {
+    self.0.lock().unwrap().push(wal_id);
+  }
+}
+
+fn pos(ver: u64) -> Pos {
+  Pos::new(ver, Flag::INFILE, 0, ver, 1)
+}
+
+#[compio::test]
+async fn test_wal_recover() -> Void {
+  let dir = tempdir()?;
+
+  // Write without flushing, then "crash" by dropping
+  // 写入但不刷盘，然后丢弃以模拟崩溃
+  {
+    let mut mem = Mem::new(usize::MAX, MockSst::default(), MockDiscard);
+    mem.recover(dir.path(), 0).await?;
+    mem.put(b"a".as_slice(), pos(1)).await?;
+    let mut batch = WriteBatch::new();
+    batch.put(b"b", pos(2)).put(b"c", pos(3));
+    mem.write(&batch).await?;
+    mem
+      .delete_range(b"c".as_slice()..b"d".as_slice(), pos(4))
+      .await?;
+    mem.delete(b"a".as_slice(), pos(5)).await?;
+    mem.sync_wal().await?;
+  }
+
+  let sst = MockSst::default();
+  let pushed = sst.0.clone();
+  let mut mem = Mem::new(usize::MAX, sst, MockDiscard);
+  mem.recover(dir.path(), 0).await?;
+  assert!(mem.get(b"a".as_slice()).unwrap().is_tombstone());
+  assert_eq!(mem.get(b"b".as_slice()), Some(pos(2)));
+  assert_eq!(mem.get(b"c".as_slice()).map(|p| p.ver), Some(4));
+
+  // Flushing hands the log id to the manifest, the log stays until the manifest is durable
+  // 刷盘将日志 id 交给清单，日志保留到清单持久化为止
+  mem.put(b"e".as_slice(), pos(6)).await?;
+  mem.rotate()?;
+  mem.sync().await?;
+  assert_eq!(*pushed.lock().unwrap(), [1, 2]);
+  assert!(dir.path().join("1.wal").exists());
+  mem.gc_wal(1).await;
+  assert!(!dir.path().join("1.wal").exists());
+  assert!(dir.path().join("2.wal").exists());
+  mem.gc_wal(2).await;
+  assert!(!dir.path().join("2.wal").exists());
+  drop(mem);
+
+  // Nothing newer than the flushed log to replay
+  // 没有比已刷盘日志更新的内容可回放
+  let mut mem = Mem::new(usize::MAX, MockSst::default(), MockDiscard);
+  mem.recover(dir.path(), 2).await?;
+  assert!(mem.freeze.is_empty());
+  assert_eq!(mem.now.wal_id, 3);
+  OK
+}
+
+#[compio::test]
+async fn test_wal_torn_tail() -> Void {
+  let dir = tempdir()?;
+  {
+    let mut mem = Mem::new(usize::MAX, MockSst::default(), MockDiscard);
+    mem.recover(dir.path(), 0).await?;
+    mem.put(b"a".as_slice(), pos(1)).await?;
+    mem.put(b"b".as_slice(), pos(2)).await?;
+    mem.sync_wal().await?;
+  }
+
+  // Half-written record after a crash
+  // 崩溃后写了一半的记录
+  let file = dir.path().join("1.wal");
+  let bin = std::fs::read(&file)?;
+  std::fs::OpenOptions::new()
+    .append(true)
+    .open(&file)?
+    .write_all(&bin[..bin.len() / 2 + 1])?;
+
+  // Logs that replay to nothing are removed instead of flushed as empty SSTs
+  // 回放为空的日志被删除，而不是刷成空 SST
+  let empty = dir.path().join("2.wal");
+  std::fs::write(&empty, [])?;
+  let torn = dir.path().join("3.wal");
+  std::fs::write(&torn, &bin[..3])?;
+
+  let sst = MockSst::default();
+  let pushed = sst.0.clone();
+  let mut mem = Mem::new(usize::MAX, sst, MockDiscard);
+  mem.recover(dir.path(), 0).await?;
+  assert!(!empty.exists());
+  assert!(!torn.exists());
+  assert_eq!(mem.now.wal_id, 4);
+  assert_eq!(mem.freeze.len(), 1);
+  assert_eq!(mem.freeze[0].table().len(), 2);
+  assert_eq!(mem.get(b"b".as_slice()), Some(pos(2)));
+  mem.sync().await?;
+  assert_eq!(*pushed.lock().unwrap(), [1]);
+  OK
+}